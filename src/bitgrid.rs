@@ -0,0 +1,316 @@
+use crate::grid::{Coord, Grid};
+use itertools::iproduct;
+
+// Enough bit planes to count the 80 neighbours of a 4D cell.
+const PLANES: usize = 7;
+
+// Birth/survival rule for life-like automata, bit `n` of each mask is set when
+// a cell with `n` live neighbours is born/survives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rule {
+    birth: u128,
+    survival: u128,
+}
+
+impl Rule {
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        if let Some(n) = birth.iter().chain(survival).find(|&&n| n > 80) {
+            panic!("{} neighbours is more than a 4D cell has", n);
+        }
+        let mask = |counts: &[usize]| counts.iter().fold(0u128, |acc, &n| acc | 1 << n);
+        Rule {
+            birth: mask(birth),
+            survival: mask(survival),
+        }
+    }
+
+    pub fn conway() -> Self {
+        Rule::new(&[3], &[2, 3])
+    }
+}
+
+// One bit per cell, rows packed into u64 words along x. Up to four dimensions
+// are supported, 2D grids simply have a single z/w layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitGrid {
+    dims: [usize; 4],
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(w: usize, h: usize) -> Self {
+        BitGrid::with_dims([w, h, 1, 1])
+    }
+
+    pub fn with_dims(dims: [usize; 4]) -> Self {
+        let words_per_row = dims[0].div_ceil(64);
+        BitGrid {
+            dims,
+            words_per_row,
+            bits: vec![0; words_per_row * dims[1] * dims[2] * dims[3]],
+        }
+    }
+
+    pub fn from_grid(grid: &Grid, alive: char) -> Self {
+        let mut result = BitGrid::new(grid.w, grid.h);
+        for (c, _) in grid.iter().filter(|&(_, &v)| v == alive) {
+            result.set(c, true);
+        }
+        result
+    }
+
+    pub fn to_grid(&self, alive: char, dead: char) -> Grid {
+        let mut grid = Grid::new(self.dims[0], self.dims[1], dead);
+        for [x, y, _, _] in self.active().filter(|p| p[2] == 0 && p[3] == 0) {
+            grid.set((x, y), alive);
+        }
+        grid
+    }
+
+    fn row_index(&self, y: usize, z: usize, w: usize) -> usize {
+        ((w * self.dims[2] + z) * self.dims[1] + y) * self.words_per_row
+    }
+
+    #[cfg(test)]
+    pub fn get_at(&self, p: [usize; 4]) -> Option<bool> {
+        if p.iter().zip(self.dims.iter()).any(|(&v, &max)| v >= max) {
+            return None;
+        }
+        let word = self.bits[self.row_index(p[1], p[2], p[3]) + p[0] / 64];
+        Some(word >> (p[0] % 64) & 1 == 1)
+    }
+
+    pub fn set_at(&mut self, p: [usize; 4], alive: bool) {
+        if p.iter().zip(self.dims.iter()).any(|(&v, &max)| v >= max) {
+            return;
+        }
+        let i = self.row_index(p[1], p[2], p[3]) + p[0] / 64;
+        if alive {
+            self.bits[i] |= 1 << (p[0] % 64);
+        } else {
+            self.bits[i] &= !(1 << (p[0] % 64));
+        }
+    }
+
+    #[cfg(test)]
+    pub fn get(&self, c: impl Coord) -> Option<bool> {
+        self.get_at([c.x(), c.y(), 0, 0])
+    }
+
+    pub fn set(&mut self, c: impl Coord, alive: bool) {
+        self.set_at([c.x(), c.y(), 0, 0], alive)
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    // The plain neighbour count `step` is checked against.
+    #[cfg(test)]
+    pub fn count_neighbours_at(&self, p: [usize; 4]) -> usize {
+        let span = |axis: usize| {
+            let lo = p[axis].saturating_sub(1);
+            let hi = (p[axis] + 1).min(self.dims[axis] - 1);
+            lo..=hi
+        };
+        iproduct!(span(0), span(1), span(2), span(3))
+            .map(|(x, y, z, w)| [x, y, z, w])
+            .filter(|&q| q != p && self.get_at(q) == Some(true))
+            .count()
+    }
+
+    #[cfg(test)]
+    pub fn count_adjacents(&self, c: impl Coord) -> usize {
+        self.count_neighbours_at([c.x(), c.y(), 0, 0])
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = [usize; 4]> + '_ {
+        iproduct!(0..self.dims[3], 0..self.dims[2], 0..self.dims[1]).flat_map(move |(w, z, y)| {
+            let row = self.row_index(y, z, w);
+            (0..self.words_per_row).flat_map(move |i| {
+                let word = self.bits[row + i];
+                (0..64)
+                    .filter(move |b| word >> b & 1 == 1)
+                    .map(move |b| [i * 64 + b, y, z, w])
+            })
+        })
+    }
+
+    // Word `i` of the row with every bit moved one cell right/left, so bit x
+    // holds the cell at x - 1/x + 1.
+    fn shifted_words(&self, row: usize, i: usize) -> [u64; 3] {
+        let word = self.bits[row + i];
+        let before = if i > 0 { self.bits[row + i - 1] } else { 0 };
+        let after = if i + 1 < self.words_per_row {
+            self.bits[row + i + 1]
+        } else {
+            0
+        };
+        [word, word << 1 | before >> 63, word >> 1 | after << 63]
+    }
+
+    // Computes the next generation 64 cells at a time: the neighbours of a
+    // word are summed into bit-sliced counters, then the rule is applied to
+    // every count at once with plain boolean operations.
+    pub fn step(&self, rule: &Rule) -> BitGrid {
+        let mut next = BitGrid::with_dims(self.dims);
+        let last_mask = match self.dims[0] % 64 {
+            0 => !0,
+            n => (1 << n) - 1,
+        };
+        let counts: Vec<usize> = (0..128)
+            .filter(|&n| (rule.birth | rule.survival) >> n & 1 == 1)
+            .collect();
+        let span = |v: usize, axis: usize| {
+            v.saturating_sub(1)..=(v + 1).min(self.dims[axis].saturating_sub(1))
+        };
+
+        for (w, z, y) in iproduct!(0..self.dims[3], 0..self.dims[2], 0..self.dims[1]) {
+            let row = self.row_index(y, z, w);
+            for i in 0..self.words_per_row {
+                let mut planes = [0u64; PLANES];
+                for (nw, nz, ny) in iproduct!(span(w, 3), span(z, 2), span(y, 1)) {
+                    let is_own_row = (nw, nz, ny) == (w, z, y);
+                    let words = self.shifted_words(self.row_index(ny, nz, nw), i);
+                    for (j, &word) in words.iter().enumerate() {
+                        if !(is_own_row && j == 0) {
+                            add(&mut planes, word);
+                        }
+                    }
+                }
+
+                let alive = self.bits[row + i];
+                let mut born = 0;
+                let mut survives = 0;
+                for &n in counts.iter() {
+                    let matches = equals(&planes, n);
+                    if rule.birth >> n & 1 == 1 {
+                        born |= matches;
+                    }
+                    if rule.survival >> n & 1 == 1 {
+                        survives |= matches;
+                    }
+                }
+
+                let mut word = (alive & survives) | (!alive & born);
+                if i + 1 == self.words_per_row {
+                    word &= last_mask;
+                }
+                next.bits[row + i] = word;
+            }
+        }
+        next
+    }
+}
+
+// Ripple-carry addition of one bit per lane into the counters.
+fn add(planes: &mut [u64; PLANES], mut carry: u64) {
+    for plane in planes.iter_mut() {
+        let overflow = *plane & carry;
+        *plane ^= carry;
+        carry = overflow;
+        if carry == 0 {
+            break;
+        }
+    }
+}
+
+// Lanes whose counter equals `n`.
+fn equals(planes: &[u64; PLANES], n: usize) -> u64 {
+    planes.iter().enumerate().fold(!0, |acc, (p, &plane)| {
+        if n >> p & 1 == 1 {
+            acc & plane
+        } else {
+            acc & !plane
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Simulation;
+
+    fn life(g: &Grid, c: (usize, usize), current: char) -> char {
+        match (current, g.count_adjacents(c, '#')) {
+            ('#', 2) | (_, 3) => '#',
+            _ => '.',
+        }
+    }
+
+    #[test]
+    fn test_matches_grid() {
+        let grid: Grid = "..##.......\n\
+            #...#...#..\n\
+            .#....#..#.\n\
+            ..#.#...#.#\n\
+            .#...##..#.\n\
+            ..#.##....."
+            .parse()
+            .unwrap();
+        let mut bits = BitGrid::from_grid(&grid, '#');
+
+        assert_eq!(grid.count_type('#'), bits.count());
+        assert_eq!(grid, bits.to_grid('#', '.'));
+        for (c, _) in grid.iter() {
+            assert_eq!(grid.count_adjacents(c, '#'), bits.count_adjacents(c));
+        }
+
+        let mut simulation = Simulation::new(grid, life);
+        for _ in 0..10 {
+            simulation.step();
+            bits = bits.step(&Rule::conway());
+            assert_eq!(simulation.grid(), &bits.to_grid('#', '.'));
+        }
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let mut bits = BitGrid::new(130, 3);
+        for &x in [63, 64, 65].iter() {
+            bits.set((x, 1), true);
+        }
+        assert_eq!(Some(true), bits.get((64, 1)));
+        assert_eq!(None, bits.get((130, 1)));
+
+        let vertical = bits.step(&Rule::conway());
+        assert_eq!(
+            vec![[64, 0, 0, 0], [64, 1, 0, 0], [64, 2, 0, 0]],
+            vertical.active().collect::<Vec<_>>()
+        );
+        assert_eq!(bits, vertical.step(&Rule::conway()));
+
+        let mut edge = BitGrid::new(130, 3);
+        for y in 0..3 {
+            edge.set((129, y), true);
+        }
+        assert_eq!(2, edge.step(&Rule::conway()).count());
+    }
+
+    #[test]
+    fn test_higher_dimensions() {
+        let mut cubes = BitGrid::with_dims([15, 15, 13, 1]);
+        let mut hypercubes = BitGrid::with_dims([15, 15, 13, 13]);
+        for &(x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter() {
+            cubes.set_at([x + 6, y + 6, 6, 0], true);
+            hypercubes.set_at([x + 6, y + 6, 6, 6], true);
+        }
+
+        let rule = Rule::conway();
+        assert_eq!(11, cubes.step(&rule).count());
+        assert_eq!(29, hypercubes.step(&rule).count());
+        for _ in 0..6 {
+            cubes = cubes.step(&rule);
+            hypercubes = hypercubes.step(&rule);
+        }
+        assert_eq!(112, cubes.count());
+        assert_eq!(848, hypercubes.count());
+    }
+
+    #[test]
+    #[should_panic(expected = "81 neighbours")]
+    fn test_rule_too_many_neighbours() {
+        Rule::new(&[3], &[2, 81]);
+    }
+}
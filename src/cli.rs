@@ -0,0 +1,557 @@
+// The subcommands besides solving, exporting and visualizing a day.
+use crate::bitgrid::{BitGrid, Rule};
+use crate::expr::{check_lines, parse, tokenize, Precedence, Trace};
+use crate::grid::{Grid, Outcome, Simulation};
+use crate::passport::{
+    split_batch, write_batch, write_json, BatchReport, Generator, Height, Passport, RuleSet,
+    DEFAULT_RULES,
+};
+use crate::pathfinding::{
+    a_star, bfs, chebyshev, connected_components, dijkstra, flood_fill, manhattan, Neighbourhood,
+};
+use crate::vm::{assemble, BlockGraph, Debugger, Machine};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|why| format!("couldn't open {}: {}", path, why))
+}
+
+fn read_grid(path: &str) -> Result<Grid, String> {
+    let source = read(path)?;
+    let mut widths = source.lines().map(str::len);
+    let w = widths.next().unwrap_or(0);
+    if w == 0 || widths.any(|l| l != w) {
+        return Err(format!("{} is not a rectangular grid", path));
+    }
+    source
+        .parse()
+        .map_err(|_| format!("{} is not a rectangular grid", path))
+}
+
+fn transform(grid: &Grid, operation: &str) -> Result<Grid, String> {
+    let mut parts = operation.splitn(2, '=');
+    let bad = || format!("bad transformation {:?}", operation);
+    match (parts.next().unwrap(), parts.next()) {
+        ("transpose", None) => Ok(grid.transpose()),
+        ("flip-h", None) => Ok(grid.flip_horizontal()),
+        ("flip-v", None) => Ok(grid.flip_vertical()),
+        ("rotate", None) => Ok(grid.rotate(1)),
+        ("rotate", Some(n)) => n.parse().map(|n| grid.rotate(n)).map_err(|_| bad()),
+        ("replace", Some(chars)) => match chars.chars().collect::<Vec<_>>()[..] {
+            [from, to] => {
+                let mut grid = grid.clone();
+                for (_, cell) in grid.iter_mut().filter(|(_, cell)| **cell == from) {
+                    *cell = to;
+                }
+                Ok(grid)
+            }
+            _ => Err(bad()),
+        },
+        ("crop", Some(area)) => {
+            let area: Vec<usize> = area
+                .split(',')
+                .map(|n| n.parse().map_err(|_| bad()))
+                .collect::<Result<_, _>>()?;
+            match area[..] {
+                [x, y, w, h] if x + w <= grid.w && y + h <= grid.h => Ok(grid.crop((x, y), w, h)),
+                _ => Err(format!(
+                    "{:?} isn't x,y,w,h inside the {}x{} grid",
+                    operation, grid.w, grid.h
+                )),
+            }
+        }
+        _ => Err(bad()),
+    }
+}
+
+// Options for `grid_transform`, applied to the transformed grid.
+pub struct GridOptions {
+    pub borders: bool,
+    pub count: Option<char>,
+    pub pattern: Option<String>,
+    pub wildcard: char,
+    pub mark: Option<char>,
+}
+
+// Applies every transformation in turn and prints the resulting grid, with
+// the matches of a pattern in any orientation marked and listed if asked.
+pub fn grid_transform(path: &str, operations: &[String], options: GridOptions) {
+    let mut grid = match read_grid(path) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(grid) => grid,
+    };
+    for operation in operations {
+        grid = match transform(&grid, operation) {
+            Err(why) => {
+                eprintln!("{}", why);
+                return;
+            }
+            Ok(grid) => grid,
+        };
+    }
+
+    let mut found = Vec::new();
+    if let Some(pattern_path) = &options.pattern {
+        let pattern = match read_grid(pattern_path) {
+            Err(why) => {
+                eprintln!("{}", why);
+                return;
+            }
+            Ok(pattern) => pattern,
+        };
+        found = grid.find_pattern_oriented(&pattern, options.wildcard);
+        if let Some(mark) = options.mark {
+            for m in found.iter() {
+                grid.mark_pattern(&pattern, m, options.wildcard, mark);
+            }
+        }
+    }
+
+    print!("{}", grid);
+    for m in found.iter() {
+        let flipped = if m.orientation.flipped {
+            ", flipped"
+        } else {
+            ""
+        };
+        println!(
+            "match at {:?}, {} quarter turns{}",
+            m.position, m.orientation.quarter_turns, flipped
+        );
+    }
+    if let Some(target) = options.count {
+        match grid.find(target) {
+            Some(first) => println!(
+                "{} cells are {:?}, the first at {:?}",
+                grid.positions(target).count(),
+                target,
+                first
+            ),
+            None => println!("no cells are {:?}", target),
+        }
+    }
+    if options.borders {
+        let sides = ["top", "right", "bottom", "left"];
+        for (side, border) in sides.iter().zip(grid.borders().iter()) {
+            println!("{}: {}", side, border.iter().collect::<String>());
+        }
+    }
+}
+
+// Finds the cheapest path from `S` to `E` around `#` walls and draws it with
+// `o`. Entering a digit costs that much, any other cell 1.
+pub fn maze(path: &str, search: &str, diagonal: bool, regions: bool) {
+    let grid = match read_grid(path) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(grid) => grid,
+    };
+    let (start, goal) = match (grid.find('S'), grid.find('E')) {
+        (Some(start), Some(goal)) => (start, goal),
+        _ => {
+            eprintln!("{} needs an S and an E", path);
+            return;
+        }
+    };
+
+    let neighbourhood = if diagonal {
+        Neighbourhood::Moore
+    } else {
+        Neighbourhood::VonNeumann
+    };
+    let open = |c: char| c != '#';
+    let cost = |g: &Grid, _, next: (usize, usize)| {
+        let digit = g.get(next).and_then(|c| c.to_digit(10)).unwrap_or(1);
+        Some(digit.max(1) as usize)
+    };
+    let found = match search {
+        "bfs" => bfs(&grid, start, goal, neighbourhood, open),
+        "dijkstra" => dijkstra(&grid, start, goal, neighbourhood, open, cost),
+        "a-star" => {
+            let distance = if diagonal { chebyshev } else { manhattan };
+            a_star(&grid, start, goal, neighbourhood, open, cost, |c| {
+                distance(c, goal)
+            })
+        }
+        other => {
+            eprintln!("unknown search {:?}, try bfs, dijkstra or a-star", other);
+            return;
+        }
+    };
+
+    match found {
+        Some(found) => {
+            let marked = grid.map(|c, cell| {
+                if cell != 'S' && cell != 'E' && found.cells.contains(&c) {
+                    'o'
+                } else {
+                    cell
+                }
+            });
+            print!("{}", marked);
+            println!("cost {}, {} steps", found.cost, found.cells.len() - 1);
+        }
+        None => {
+            let reachable = flood_fill(&grid, start, neighbourhood, open);
+            println!("no path, {} cells are reachable from S", reachable.len());
+        }
+    }
+
+    if regions {
+        let components = connected_components(&grid, neighbourhood, open);
+        println!("{} regions", components.count);
+        for label in 0..components.count {
+            println!(
+                "region {}: {} cells",
+                label,
+                components.members(label).len()
+            );
+        }
+        if let Some(label) = components.label(start) {
+            println!("S is in region {}", label);
+        }
+    }
+}
+
+// Conway's game of life on a grid file, `#` is a live cell and anything else
+// a dead one. Stops once the grid settles, repeats or after `generations`.
+pub fn life(path: &str, generations: usize, bits: bool) {
+    let grid = match read_grid(path) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(grid) => grid,
+    };
+    if bits {
+        return life_bits(&grid, generations);
+    }
+
+    let rule = |g: &Grid, c: (usize, usize), cell: char| match (cell, g.count_adjacents(c, '#')) {
+        ('#', 2) | (_, 3) => '#',
+        _ => '.',
+    };
+    let mut simulation = Simulation::new(grid, rule)
+        .track_changes(Grid::adjacent_coords)
+        .detect_cycles();
+    let outcome = simulation.run_for(generations);
+
+    print!("{}", simulation.grid());
+    println!(
+        "generation {}, {} alive",
+        simulation.generation(),
+        simulation.grid().count_type('#')
+    );
+    match outcome {
+        Outcome::FixedPoint { .. } => println!("settled"),
+        Outcome::Cycle { start, period } => {
+            println!(
+                "repeats every {} generations from generation {}",
+                period, start
+            )
+        }
+        Outcome::StepLimit { .. } => println!("still changing"),
+    }
+}
+
+// Same rules on the packed grid, which is much faster on big inputs but only
+// notices when the grid stops changing, not when it starts repeating.
+fn life_bits(grid: &Grid, generations: usize) {
+    let rule = Rule::conway();
+    let mut bits = BitGrid::from_grid(grid, '#');
+    let mut generation = 0;
+    let mut settled = false;
+    while generation < generations && !settled {
+        let next = bits.step(&rule);
+        settled = next == bits;
+        if !settled {
+            generation += 1;
+        }
+        bits = next;
+    }
+
+    print!("{}", bits.to_grid('#', '.'));
+    println!("generation {}, {} alive", generation, bits.count());
+    println!("{}", if settled { "settled" } else { "still changing" });
+}
+
+pub fn passport_report(path: Option<&str>, rules_path: Option<&str>, verbose: bool) {
+    let batch = match read(path.unwrap_or("inputs/day4.txt")) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(batch) => batch,
+    };
+    let rules = match rules_path.map(|p| RuleSet::load(Path::new(p))) {
+        Some(Err(why)) => {
+            eprintln!("couldn't load rules: {}", why);
+            return;
+        }
+        Some(Ok(rules)) => rules,
+        None => DEFAULT_RULES.clone(),
+    };
+
+    let report = BatchReport::new(&batch, &rules);
+    if verbose {
+        for (i, errors) in report.failures.iter() {
+            println!("passport {}:", i);
+            for e in errors {
+                println!("  {}", e);
+            }
+        }
+    }
+    print!("{}", report);
+}
+
+// Rewrites the valid passports of a batch in canonical order, or as JSON.
+pub fn passport_normalise(path: Option<&str>, json: bool, units: Option<&str>) {
+    let convert: fn(Height) -> Height = match units {
+        None => |h| h,
+        Some("cm") => Height::to_centimeters,
+        Some("in") => Height::to_inches,
+        Some(other) => {
+            eprintln!("unknown unit {}, expected cm or in", other);
+            return;
+        }
+    };
+    let batch = match read(path.unwrap_or("inputs/day4.txt")) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(batch) => batch,
+    };
+    let mut passports: Vec<Passport> = split_batch(&batch).filter_map(|p| p.parse().ok()).collect();
+    for passport in passports.iter_mut() {
+        passport.hgt = convert(passport.hgt);
+    }
+
+    let stdout = io::stdout();
+    let result = if json {
+        write_json(stdout.lock(), &passports)
+    } else {
+        write_batch(stdout.lock(), &passports)
+    };
+    if let Err(why) = result {
+        eprintln!("couldn't write passports: {}", why);
+    }
+    eprintln!(
+        "{} valid passports, {} skipped",
+        passports.len(),
+        split_batch(&batch).count() - passports.len()
+    );
+    let heights = passports.iter().map(|p| p.hgt);
+    if let (Some(shortest), Some(tallest)) = (
+        heights.clone().min_by(|a, b| a.cmp_length(*b)),
+        heights.max_by(|a, b| a.cmp_length(*b)),
+    ) {
+        eprintln!("heights from {} to {}", shortest, tallest);
+    }
+}
+
+// A reproducible batch for the puzzle rules, the intended failure of every
+// invalid passport goes to stderr.
+pub fn passport_generate(count: usize, seed: u64, invalid_ratio: f64) {
+    let samples = Generator::new(seed).batch(count, invalid_ratio);
+    if let Err(why) = write_batch(io::stdout().lock(), &samples) {
+        eprintln!("couldn't write passports: {}", why);
+    }
+    for (i, sample) in samples.iter().enumerate() {
+        if let Some(defect) = sample.defect {
+            eprintln!("passport {}: {}", i + 1, defect);
+        }
+    }
+}
+
+// Options for `debug`, applied before the first command is read.
+pub struct DebugOptions {
+    pub breakpoints: Vec<usize>,
+    pub watch: bool,
+    pub step_limit: Option<usize>,
+    pub detect_loops: bool,
+}
+
+// Reads commands from stdin until it ends or `quit`, so a session can also be
+// piped in.
+pub fn debug(path: &str, options: DebugOptions) {
+    let source = match read(path) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(source) => source,
+    };
+    let program = match assemble(&source) {
+        Err(why) => {
+            eprintln!("{}: {}", path, why);
+            return;
+        }
+        Ok(program) => program,
+    };
+
+    let mut machine = Machine::new(program).detect_loops(options.detect_loops);
+    if let Some(limit) = options.step_limit {
+        machine = machine.step_limit(limit);
+    }
+    let mut debugger = Debugger::new(machine);
+    for pc in options.breakpoints {
+        debugger.add_breakpoint(pc);
+    }
+    debugger.watch_acc(options.watch);
+
+    let stdin = io::stdin();
+    let mut out = io::stdout();
+    loop {
+        print!("(debug) ");
+        out.flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            println!();
+            break;
+        }
+        if !debugger.execute(&line, &mut out).unwrap() {
+            break;
+        }
+    }
+}
+
+// Writes the control-flow graph of a day 8 program as Graphviz DOT, to stdout
+// unless a path is given, and lists its cycles and unreachable blocks.
+pub fn control_flow(path: &str, output: Option<&str>) {
+    let source = match read(path) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(source) => source,
+    };
+    let program = match assemble(&source) {
+        Err(why) => {
+            eprintln!("{}: {}", path, why);
+            return;
+        }
+        Ok(program) => program,
+    };
+
+    let graph = BlockGraph::new(&program);
+    let dot = graph.to_dot();
+    match output {
+        Some(output) => {
+            if let Err(why) = fs::write(output, dot) {
+                eprintln!("couldn't write {}: {}", output, why);
+                return;
+            }
+        }
+        None => print!("{}", dot),
+    }
+
+    let starts = |blocks: &[usize]| -> String {
+        let pcs: Vec<String> = blocks
+            .iter()
+            .map(|&b| graph.blocks[b].start.to_string())
+            .collect();
+        pcs.join(" -> ")
+    };
+    eprintln!("{} blocks", graph.blocks.len());
+    for cycle in graph.cycles() {
+        eprintln!("cycle: {}", starts(&cycle));
+    }
+    for block in graph.unreachable() {
+        let block = graph.blocks[block];
+        eprintln!("unreachable: {}..{}", block.start, block.end);
+    }
+}
+
+fn homework_mode(mode: &str) -> Option<Precedence> {
+    match mode {
+        "flat" => Some(Precedence::flat()),
+        "addition" => Some(Precedence::addition_first()),
+        "arithmetic" => Some(Precedence::arithmetic()),
+        other => {
+            eprintln!("unknown mode {:?}, try flat, addition or arithmetic", other);
+            None
+        }
+    }
+}
+
+// Lists every line of a day 18 homework file that doesn't tokenize, parse or
+// evaluate under the given rules, with the offending column underlined.
+pub fn homework(path: &str, mode: &str) {
+    let precedence = match homework_mode(mode) {
+        Some(precedence) => precedence,
+        None => return,
+    };
+    let source = match read(path) {
+        Err(why) => {
+            eprintln!("{}", why);
+            return;
+        }
+        Ok(source) => source,
+    };
+
+    let errors = check_lines(&source, &precedence);
+    for (line_no, line, error) in errors.iter() {
+        println!("{}:{}:{}", path, line_no, error.span.start + 1);
+        println!("{}\n", error.render(line));
+    }
+    let total = source.lines().filter(|l| !l.trim().is_empty()).count();
+    println!("{} of {} lines are invalid", errors.len(), total);
+}
+
+// Prints one day 18 expression, given as is or as a line number of the
+// homework file, fully parenthesised and reduced one operation at a time.
+// Without a mode it's shown under both puzzle rules.
+pub fn explain(expression: &str, path: &str, mode: Option<&str>) {
+    let line = match expression.parse::<usize>() {
+        Ok(line_no) => {
+            let source = match read(path) {
+                Err(why) => {
+                    eprintln!("{}", why);
+                    return;
+                }
+                Ok(source) => source,
+            };
+            match source.lines().nth(line_no.wrapping_sub(1)) {
+                Some(line) => line.to_string(),
+                None => {
+                    eprintln!("{} has no line {}", path, line_no);
+                    return;
+                }
+            }
+        }
+        Err(_) => expression.to_string(),
+    };
+    let modes = match mode {
+        Some(mode) => vec![mode],
+        None => vec!["flat", "addition"],
+    };
+
+    let tokens = match tokenize(&line) {
+        Err(why) => {
+            println!("{}", why.render(&line));
+            return;
+        }
+        Ok(tokens) => tokens,
+    };
+    for mode in modes {
+        let precedence = match homework_mode(mode) {
+            Some(precedence) => precedence,
+            None => return,
+        };
+        println!("{}:", mode);
+        match parse(&tokens, &precedence) {
+            Err(why) => println!("{}", why.render(&line)),
+            Ok(expr) => print!("{}", Trace::new(&expr)),
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use crate::grid::Grid;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    Png(png::EncodingError),
+    Gif(gif::EncodingError),
+    TooManyColours,
+    TooLarge,
+    NoFrames,
+    // Frame number, its size and the size of the first frame.
+    FrameSize(usize, (usize, usize), (usize, usize)),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "{}", e),
+            ExportError::Png(e) => write!(f, "png: {}", e),
+            ExportError::Gif(e) => write!(f, "gif: {}", e),
+            ExportError::TooManyColours => write!(f, "more than 256 colours"),
+            ExportError::TooLarge => write!(f, "image too large"),
+            ExportError::NoFrames => write!(f, "no frames to write"),
+            ExportError::FrameSize(i, (w, h), (first_w, first_h)) => write!(
+                f,
+                "frame {} is {}x{} but the first one is {}x{}",
+                i, w, h, first_w, first_h
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for ExportError {
+    fn from(e: png::EncodingError) -> Self {
+        ExportError::Png(e)
+    }
+}
+
+impl From<gif::EncodingError> for ExportError {
+    fn from(e: gif::EncodingError) -> Self {
+        ExportError::Gif(e)
+    }
+}
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colours: HashMap<char, Rgb>,
+    fallback: Rgb,
+    scale: usize,
+}
+
+impl Palette {
+    pub fn new(fallback: Rgb) -> Self {
+        Palette {
+            colours: HashMap::new(),
+            fallback,
+            scale: 1,
+        }
+    }
+
+    pub fn with(mut self, c: char, colour: Rgb) -> Self {
+        self.colours.insert(c, colour);
+        self
+    }
+
+    // Every cell becomes a `scale` x `scale` square of pixels.
+    pub fn scaled(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn colour(&self, c: char) -> Rgb {
+        *self.colours.get(&c).unwrap_or(&self.fallback)
+    }
+}
+
+// Floor, empty seats and occupied seats/active cubes.
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::new([255, 0, 255])
+            .with('.', [235, 235, 235])
+            .with('L', [90, 160, 90])
+            .with('#', [40, 40, 40])
+            .scaled(4)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub w: usize,
+    pub h: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn from_grid(grid: &Grid, palette: &Palette) -> Self {
+        let scale = palette.scale;
+        let (w, h) = (grid.w * scale, grid.h * scale);
+        let pixels = (0..w * h)
+            .map(|i| (i % w / scale, i / w / scale))
+            .map(|c| palette.colour(*grid.get(c).unwrap()))
+            .collect();
+
+        Image { w, h, pixels }
+    }
+
+    #[cfg(test)]
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[x + y * self.w]
+    }
+
+    fn raw(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|p| p.iter().cloned()).collect()
+    }
+
+    pub fn write_ppm<W: Write>(&self, mut w: W) -> Result<(), ExportError> {
+        write!(w, "P6\n{} {}\n255\n", self.w, self.h)?;
+        w.write_all(&self.raw())?;
+        Ok(())
+    }
+
+    pub fn write_png<W: Write>(&self, w: W) -> Result<(), ExportError> {
+        let mut encoder = png::Encoder::new(w, self.w as u32, self.h as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.raw())?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    // The format is picked from the extension, anything but `.png` is PPM.
+    pub fn save(&self, path: &Path) -> Result<(), ExportError> {
+        let file = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => self.write_png(file),
+            _ => self.write_ppm(file),
+        }
+    }
+}
+
+// Bounding box of the x/y coordinates, shared by every slice of an animation
+// so all frames end up the same size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: (isize, isize),
+    pub max: (isize, isize),
+}
+
+impl Bounds {
+    pub fn of<'a, I>(points: I) -> Option<Bounds>
+    where
+        I: IntoIterator<Item = &'a (isize, isize, isize, isize)>,
+    {
+        points.into_iter().fold(None, |acc, &(x, y, _, _)| {
+            let b = acc.unwrap_or(Bounds {
+                min: (x, y),
+                max: (x, y),
+            });
+            Some(Bounds {
+                min: (b.min.0.min(x), b.min.1.min(y)),
+                max: (b.max.0.max(x), b.max.1.max(y)),
+            })
+        })
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: (self.min.0.min(other.min.0), self.min.1.min(other.min.1)),
+            max: (self.max.0.max(other.max.0), self.max.1.max(other.max.1)),
+        }
+    }
+}
+
+// Renders the z/w layer of a sparse set of active cells as '#' on '.'.
+pub fn sparse_slice(
+    cells: &HashSet<(isize, isize, isize, isize)>,
+    z: isize,
+    w: isize,
+    bounds: &Bounds,
+) -> Grid {
+    let mut grid = Grid::new(
+        (bounds.max.0 - bounds.min.0 + 1) as usize,
+        (bounds.max.1 - bounds.min.1 + 1) as usize,
+        '.',
+    );
+    for &(x, y, _, _) in cells.iter().filter(|&&(_, _, k, l)| k == z && l == w) {
+        if x >= bounds.min.0 && x <= bounds.max.0 && y >= bounds.min.1 && y <= bounds.max.1 {
+            let c = ((x - bounds.min.0) as usize, (y - bounds.min.1) as usize);
+            grid.set(c, '#');
+        }
+    }
+    grid
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Animation {
+    frames: Vec<Image>,
+    // Hundredths of a second per frame, as GIF expects.
+    pub delay: u16,
+}
+
+impl Animation {
+    pub fn new(delay: u16) -> Self {
+        Animation {
+            frames: Vec::new(),
+            delay,
+        }
+    }
+
+    pub fn push(&mut self, frame: Image) {
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn write_gif<W: Write>(&self, w: W) -> Result<(), ExportError> {
+        let first = self.frames.first().ok_or(ExportError::NoFrames)?;
+        if first.w > u16::MAX as usize || first.h > u16::MAX as usize {
+            return Err(ExportError::TooLarge);
+        }
+        // Every frame is drawn on the first one's canvas.
+        if let Some((i, frame)) = self
+            .frames
+            .iter()
+            .enumerate()
+            .find(|(_, f)| (f.w, f.h) != (first.w, first.h))
+        {
+            return Err(ExportError::FrameSize(
+                i,
+                (frame.w, frame.h),
+                (first.w, first.h),
+            ));
+        }
+
+        let mut colours: Vec<Rgb> = Vec::new();
+        let mut indices: HashMap<Rgb, u8> = HashMap::new();
+        for &p in self.frames.iter().flat_map(|f| f.pixels.iter()) {
+            if let Entry::Vacant(entry) = indices.entry(p) {
+                if colours.len() == 256 {
+                    return Err(ExportError::TooManyColours);
+                }
+                entry.insert(colours.len() as u8);
+                colours.push(p);
+            }
+        }
+
+        let global: Vec<u8> = colours.iter().flat_map(|c| c.iter().cloned()).collect();
+        let mut encoder = gif::Encoder::new(w, first.w as u16, first.h as u16, &global)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for image in self.frames.iter() {
+            let pixels: Vec<u8> = image.pixels.iter().map(|p| indices[p]).collect();
+            let mut frame =
+                gif::Frame::from_indexed_pixels(image.w as u16, image.h as u16, pixels, None);
+            frame.delay = self.delay;
+            encoder.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    // One numbered image per frame, in whichever format `extension` names.
+    pub fn write_frames(&self, dir: &Path, extension: &str) -> Result<(), ExportError> {
+        if self.is_empty() {
+            return Err(ExportError::NoFrames);
+        }
+        fs::create_dir_all(dir)?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            frame.save(&dir.join(format!("frame_{:04}.{}", i, extension)))?;
+        }
+        Ok(())
+    }
+
+    // `.gif` paths get a single animated file, anything else a frame directory.
+    pub fn save(&self, path: &Path) -> Result<(), ExportError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gif") => self.write_gif(BufWriter::new(File::create(path)?)),
+            _ => self.write_frames(path, "png"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_grid() {
+        let grid: Grid = "#.\nL#".parse().unwrap();
+        let palette = Palette::new([0, 0, 255])
+            .with('#', [255, 0, 0])
+            .with('.', [0, 255, 0])
+            .scaled(2);
+        let image = Image::from_grid(&grid, &palette);
+
+        assert_eq!((4, 4), (image.w, image.h));
+        assert_eq!([255, 0, 0], image.pixel(1, 1));
+        assert_eq!([0, 255, 0], image.pixel(2, 1));
+        assert_eq!([0, 0, 255], image.pixel(0, 3));
+
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(11 + 4 * 4 * 3, ppm.len());
+
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+    }
+
+    #[test]
+    fn test_sparse_slice() {
+        let cells: HashSet<_> = vec![(-1, 0, 0, 0), (1, 2, 0, 0), (0, 0, 1, 0)]
+            .into_iter()
+            .collect();
+        let bounds = Bounds::of(&cells).unwrap();
+
+        assert_eq!((-1, 0), bounds.min);
+        assert_eq!((1, 2), bounds.max);
+        assert_eq!(
+            "#..\n...\n..#\n",
+            sparse_slice(&cells, 0, 0, &bounds).to_string()
+        );
+        assert_eq!(
+            ".#.\n...\n...\n",
+            sparse_slice(&cells, 1, 0, &bounds).to_string()
+        );
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut animation = Animation::new(10);
+        let mut gif = Vec::new();
+        assert!(animation.write_gif(&mut gif).is_err());
+
+        for g in ["#.\n..", ".#\n..", "..\n.#"].iter() {
+            animation.push(Image::from_grid(&g.parse().unwrap(), &Palette::default()));
+        }
+        animation.write_gif(&mut gif).unwrap();
+
+        assert_eq!(3, animation.len());
+        assert_eq!(b"GIF89a", &gif[..6]);
+        assert_eq!(Some(&0x3b), gif.last());
+
+        animation.push(Image::from_grid(&"#".parse().unwrap(), &Palette::default()));
+        let error = animation.write_gif(Vec::new()).unwrap_err();
+        assert_eq!("frame 3 is 4x4 but the first one is 8x8", error.to_string());
+        assert!(Animation::new(10)
+            .write_frames(Path::new("unused"), "png")
+            .is_err());
+    }
+}
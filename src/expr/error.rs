@@ -0,0 +1,88 @@
+use super::lexer::{Span, TokenKind};
+use std::fmt::{self, Display, Formatter};
+
+// What the parser was looking for when it gave up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    Operand,
+    ClosingParen,
+    OperatorOrEnd,
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Operand => write!(f, "a number, '-' or '('"),
+            Expected::ClosingParen => write!(f, "an operator or ')'"),
+            Expected::OperatorOrEnd => write!(f, "an operator or the end of the line"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnexpectedChar(char),
+    // `found` is `None` at the end of the line.
+    Unexpected {
+        expected: Expected,
+        found: Option<TokenKind>,
+    },
+    // The operator has no entry in the precedence table.
+    Unsupported(char),
+    Overflow,
+    DivisionByZero,
+    NegativeExponent,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            ErrorKind::Unexpected {
+                expected,
+                found: Some(found),
+            } => write!(f, "expected {}, found {}", expected, found),
+            ErrorKind::Unexpected {
+                expected,
+                found: None,
+            } => write!(f, "expected {}, found the end of the line", expected),
+            ErrorKind::Unsupported(op) => write!(f, "operator {} is not supported", op),
+            ErrorKind::Overflow => write!(f, "arithmetic overflow"),
+            ErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ErrorKind::NegativeExponent => write!(f, "negative exponent"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExprError {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl ExprError {
+    pub fn new(kind: ErrorKind, span: Span) -> Self {
+        ExprError { kind, span }
+    }
+
+    // The offending line with the span underlined:
+    //
+    //     1 + (2 * 3
+    //               ^ expected an operator or ')', found the end of the line
+    pub fn render(&self, line: &str) -> String {
+        let width = self.span.end.saturating_sub(self.span.start).max(1);
+        format!(
+            "{}\n{}{} {}",
+            line,
+            " ".repeat(self.span.start),
+            "^".repeat(width),
+            self.kind
+        )
+    }
+}
+
+impl Display for ExprError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.span.start + 1, self.kind)
+    }
+}
@@ -0,0 +1,138 @@
+use super::{ErrorKind, ExprError};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Number(i64),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    LParen,
+    RParen,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            TokenKind::Number(n) => return write!(f, "number {}", n),
+            TokenKind::Plus => '+',
+            TokenKind::Minus => '-',
+            TokenKind::Star => '*',
+            TokenKind::Slash => '/',
+            TokenKind::Caret => '^',
+            TokenKind::LParen => '(',
+            TokenKind::RParen => ')',
+        };
+        write!(f, "{:?}", symbol)
+    }
+}
+
+// Columns `start..end` of the line the token was read from, starting at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+pub fn tokenize(line: &str) -> Result<Vec<Token>, ExprError> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let kind = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '0'..='9' => {
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let digits: String = chars[start..i].iter().collect();
+                let span = Span { start, end: i };
+                let n = digits
+                    .parse()
+                    .map_err(|_| ExprError::new(ErrorKind::Overflow, span))?;
+                tokens.push(Token {
+                    kind: TokenKind::Number(n),
+                    span,
+                });
+                continue;
+            }
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '^' => TokenKind::Caret,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            c => {
+                let span = Span {
+                    start,
+                    end: start + 1,
+                };
+                return Err(ExprError::new(ErrorKind::UnexpectedChar(c), span));
+            }
+        };
+        i += 1;
+        tokens.push(Token {
+            kind,
+            span: Span { start, end: i },
+        });
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    #[test]
+    fn test_tokenize() {
+        let kinds = |line| -> Vec<TokenKind> {
+            tokenize(line)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.kind)
+                .collect()
+        };
+
+        assert_eq!(vec![Number(1), Plus, Number(2)], kinds("1 + 2"));
+        assert_eq!(
+            vec![LParen, Number(123), Star, Number(45), RParen],
+            kinds("(123*  45)")
+        );
+        assert_eq!(
+            Span { start: 2, end: 5 },
+            tokenize("( 123)").unwrap()[1].span
+        );
+        assert_eq!(
+            vec![Minus, Number(2), Caret, Number(3), Slash, Number(4)],
+            kinds("-2^3/4")
+        );
+        assert_eq!(
+            Err(ExprError::new(
+                ErrorKind::UnexpectedChar('x'),
+                Span { start: 4, end: 5 }
+            )),
+            tokenize("1 + x")
+        );
+        assert_eq!(
+            Err(ExprError::new(
+                ErrorKind::Overflow,
+                Span { start: 2, end: 22 }
+            )),
+            tokenize("1+99999999999999999999")
+        );
+    }
+}
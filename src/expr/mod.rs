@@ -0,0 +1,200 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+mod error;
+mod lexer;
+mod parser;
+mod trace;
+pub use error::{ErrorKind, ExprError};
+pub use lexer::{tokenize, Span, Token};
+pub use parser::{parse, Precedence};
+pub use trace::Trace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinOp {
+    fn from_token(kind: lexer::TokenKind) -> Option<BinOp> {
+        match kind {
+            lexer::TokenKind::Plus => Some(BinOp::Add),
+            lexer::TokenKind::Minus => Some(BinOp::Sub),
+            lexer::TokenKind::Star => Some(BinOp::Mul),
+            lexer::TokenKind::Slash => Some(BinOp::Div),
+            lexer::TokenKind::Caret => Some(BinOp::Pow),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(self) -> char {
+        match self {
+            BinOp::Add => '+',
+            BinOp::Sub => '-',
+            BinOp::Mul => '*',
+            BinOp::Div => '/',
+            BinOp::Pow => '^',
+        }
+    }
+
+    // Division truncates towards zero.
+    pub fn apply(self, a: i64, b: i64) -> Result<i64, ErrorKind> {
+        match self {
+            BinOp::Add => a.checked_add(b).ok_or(ErrorKind::Overflow),
+            BinOp::Sub => a.checked_sub(b).ok_or(ErrorKind::Overflow),
+            BinOp::Mul => a.checked_mul(b).ok_or(ErrorKind::Overflow),
+            BinOp::Div if b == 0 => Err(ErrorKind::DivisionByZero),
+            BinOp::Div => a.checked_div(b).ok_or(ErrorKind::Overflow),
+            BinOp::Pow if b < 0 => Err(ErrorKind::NegativeExponent),
+            BinOp::Pow => {
+                let b = u32::try_from(b).map_err(|_| ErrorKind::Overflow)?;
+                a.checked_pow(b).ok_or(ErrorKind::Overflow)
+            }
+        }
+    }
+}
+
+// Operators keep their span so evaluation errors can point at them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(i64),
+    Neg {
+        operand: Box<Expr>,
+        span: Span,
+    },
+    Binary {
+        op: BinOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+        span: Span,
+    },
+}
+
+impl Expr {
+    pub fn eval(&self) -> Result<i64, ExprError> {
+        match self {
+            Expr::Number(n) => Ok(*n),
+            Expr::Neg { operand, span } => operand
+                .eval()?
+                .checked_neg()
+                .ok_or_else(|| ExprError::new(ErrorKind::Overflow, *span)),
+            Expr::Binary { op, lhs, rhs, span } => op
+                .apply(lhs.eval()?, rhs.eval()?)
+                .map_err(|kind| ExprError::new(kind, *span)),
+        }
+    }
+
+    // Operands that aren't plain numbers get their own parentheses, negative
+    // numbers too so the output parses back the same under any precedence.
+    fn fmt_operand(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(n) if *n >= 0 => write!(f, "{}", n),
+            _ => write!(f, "({})", self),
+        }
+    }
+}
+
+// Fully parenthesised, apart from the outermost operation.
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Neg { operand, .. } => {
+                write!(f, "-")?;
+                operand.fmt_operand(f)
+            }
+            Expr::Binary { op, lhs, rhs, .. } => {
+                lhs.fmt_operand(f)?;
+                write!(f, " {} ", op.symbol())?;
+                rhs.fmt_operand(f)
+            }
+        }
+    }
+}
+
+// Tokenizes, parses and evaluates every non-empty line, returning the bad
+// ones with their line number (starting at 1) and error.
+pub fn check_lines<'a>(
+    source: &'a str,
+    precedence: &Precedence,
+) -> Vec<(usize, &'a str, ExprError)> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| {
+            let result = tokenize(line)
+                .and_then(|tokens| parse(&tokens, precedence))
+                .and_then(|expr| expr.eval());
+            result.err().map(|e| (i + 1, line, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let explicit = |line, precedence: &Precedence| {
+            parse(&tokenize(line).unwrap(), precedence)
+                .unwrap()
+                .to_string()
+        };
+        let line = "1 + 2 * 3 + 4 * 5 + 6";
+        assert_eq!(
+            "((((1 + 2) * 3) + 4) * 5) + 6",
+            explicit(line, &Precedence::flat())
+        );
+        assert_eq!(
+            "((1 + 2) * (3 + 4)) * (5 + 6)",
+            explicit(line, &Precedence::addition_first())
+        );
+        assert_eq!(
+            "2 ^ (3 ^ 2)",
+            explicit("2 ^ 3 ^ 2", &Precedence::arithmetic())
+        );
+        assert_eq!("-(2 ^ 2)", explicit("-2 ^ 2", &Precedence::arithmetic()));
+        assert_eq!("(-2) ^ 2", explicit("-2 ^ 2", &Precedence::flat()));
+        assert_eq!("7", explicit("((7))", &Precedence::flat()));
+
+        // The explicit form means the same thing under any rules.
+        let maths = Precedence::arithmetic();
+        let expr = parse(&tokenize(line).unwrap(), &Precedence::flat()).unwrap();
+        let reparsed = parse(&tokenize(&expr.to_string()).unwrap(), &maths).unwrap();
+        assert_eq!(expr.eval(), reparsed.eval());
+    }
+
+    #[test]
+    fn test_check_lines() {
+        let source = "1 + 2\n(3 * 4\n\n2 ^ 70 + 1\n5 $ 6\n1 + 2 3";
+        let errors = check_lines(source, &Precedence::arithmetic());
+
+        assert_eq!(
+            vec![2, 4, 5, 6],
+            errors.iter().map(|(line, _, _)| *line).collect::<Vec<_>>()
+        );
+        let rendered: Vec<String> = errors.iter().map(|(_, line, e)| e.render(line)).collect();
+        assert_eq!(
+            "(3 * 4\n      ^ expected an operator or ')', found the end of the line",
+            rendered[0]
+        );
+        assert_eq!("2 ^ 70 + 1\n  ^ arithmetic overflow", rendered[1]);
+        assert_eq!("5 $ 6\n  ^ unexpected character '$'", rendered[2]);
+        assert_eq!(
+            "1 + 2 3\n      ^ expected an operator or the end of the line, found number 3",
+            rendered[3]
+        );
+        assert_eq!(
+            "column 7: expected an operator or the end of the line, found number 3",
+            errors[3].2.to_string()
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use super::{
+    error::{ErrorKind, Expected},
+    lexer::{Span, Token, TokenKind},
+    BinOp, Expr, ExprError,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+// Binding power and associativity of each operator, higher binds tighter.
+// Operators missing from the table, unary minus included, are rejected.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Precedence {
+    levels: HashMap<BinOp, (u8, Assoc)>,
+    negation: Option<u8>,
+}
+
+impl Precedence {
+    pub fn new() -> Self {
+        Precedence::default()
+    }
+
+    pub fn with(mut self, op: BinOp, level: u8, assoc: Assoc) -> Self {
+        self.levels.insert(op, (level, assoc));
+        self
+    }
+
+    // Unary minus takes every operator binding at least this tight as its
+    // operand, so below `^` makes -2^2 == -4.
+    pub fn with_negation(mut self, level: u8) -> Self {
+        self.negation = Some(level);
+        self
+    }
+
+    // Part 1, evaluated strictly left to right.
+    pub fn flat() -> Self {
+        [BinOp::Add, BinOp::Sub, BinOp::Mul, BinOp::Div, BinOp::Pow]
+            .iter()
+            .fold(Precedence::new(), |p, &op| p.with(op, 1, Assoc::Left))
+            .with_negation(2)
+    }
+
+    // Part 2, addition and subtraction before multiplication and division.
+    pub fn addition_first() -> Self {
+        Precedence::new()
+            .with(BinOp::Mul, 1, Assoc::Left)
+            .with(BinOp::Div, 1, Assoc::Left)
+            .with(BinOp::Add, 2, Assoc::Left)
+            .with(BinOp::Sub, 2, Assoc::Left)
+            .with(BinOp::Pow, 3, Assoc::Right)
+            .with_negation(4)
+    }
+
+    // The usual school rules.
+    pub fn arithmetic() -> Self {
+        Precedence::new()
+            .with(BinOp::Add, 1, Assoc::Left)
+            .with(BinOp::Sub, 1, Assoc::Left)
+            .with(BinOp::Mul, 2, Assoc::Left)
+            .with(BinOp::Div, 2, Assoc::Left)
+            .with(BinOp::Pow, 4, Assoc::Right)
+            .with_negation(3)
+    }
+
+    pub fn level(&self, op: BinOp) -> Option<(u8, Assoc)> {
+        self.levels.get(&op).copied()
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    precedence: &'a Precedence,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<TokenKind> {
+        self.tokens.get(self.pos).map(|t| t.kind)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    // Points at `token`, or just past the last token at the end of the line.
+    fn unexpected(&self, expected: Expected, token: Option<Token>) -> ExprError {
+        let span = match token {
+            Some(token) => token.span,
+            None => {
+                let end = self.tokens.last().map_or(0, |t| t.span.end);
+                Span {
+                    start: end,
+                    end: end + 1,
+                }
+            }
+        };
+        let found = token.map(|t| t.kind);
+        ExprError::new(ErrorKind::Unexpected { expected, found }, span)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExprError> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => return Err(self.unexpected(Expected::Operand, None)),
+        };
+        match token.kind {
+            TokenKind::Number(n) => Ok(Expr::Number(n)),
+            TokenKind::Minus => {
+                let level = self
+                    .precedence
+                    .negation
+                    .ok_or_else(|| ExprError::new(ErrorKind::Unsupported('-'), token.span))?;
+                Ok(Expr::Neg {
+                    operand: Box::new(self.expression(level.into())?),
+                    span: token.span,
+                })
+            }
+            TokenKind::LParen => {
+                let inner = self.expression(0)?;
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::RParen,
+                        ..
+                    }) => Ok(inner),
+                    token => Err(self.unexpected(Expected::ClosingParen, token)),
+                }
+            }
+            _ => Err(self.unexpected(Expected::Operand, Some(token))),
+        }
+    }
+
+    // Precedence climbing: keeps folding operators that bind at least as
+    // tight as `min`. The right operand of a left associative operator only
+    // takes tighter ones, a right associative one takes its own level too.
+    // `min` is wider than a level so the tightest one, 255, still has a next.
+    fn expression(&mut self, min: u16) -> Result<Expr, ExprError> {
+        let mut lhs = self.primary()?;
+        while let Some(op) = self.peek().and_then(BinOp::from_token) {
+            let span = self.tokens[self.pos].span;
+            let (level, assoc) = self
+                .precedence
+                .level(op)
+                .ok_or_else(|| ExprError::new(ErrorKind::Unsupported(op.symbol()), span))?;
+            let level = u16::from(level);
+            if level < min {
+                break;
+            }
+            self.pos += 1;
+            let rhs = match assoc {
+                Assoc::Left => self.expression(level + 1)?,
+                Assoc::Right => self.expression(level)?,
+            };
+            lhs = Expr::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                span,
+            };
+        }
+        Ok(lhs)
+    }
+}
+
+pub fn parse(tokens: &[Token], precedence: &Precedence) -> Result<Expr, ExprError> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        precedence,
+    };
+    let expr = parser.expression(0)?;
+    match parser.next() {
+        None => Ok(expr),
+        token => Err(parser.unexpected(Expected::OperatorOrEnd, token)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::tokenize;
+
+    fn eval(line: &str, precedence: &Precedence) -> i64 {
+        parse(&tokenize(line).unwrap(), precedence)
+            .unwrap()
+            .eval()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_part_one() {
+        let flat = Precedence::flat();
+        assert_eq!(71, eval("1 + 2 * 3 + 4 * 5 + 6", &flat));
+        assert_eq!(51, eval("1 + (2 * 3) + (4 * (5 + 6))", &flat));
+        assert_eq!(26, eval("2 * 3 + (4 * 5)", &flat));
+        assert_eq!(437, eval("5 + (8 * 3 + 9 + 3 * 4 * 3)", &flat));
+        assert_eq!(
+            13632,
+            eval("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", &flat)
+        );
+    }
+
+    #[test]
+    fn test_part_two() {
+        let plus = Precedence::addition_first();
+        assert_eq!(231, eval("1 + 2 * 3 + 4 * 5 + 6", &plus));
+        assert_eq!(51, eval("1 + (2 * 3) + (4 * (5 + 6))", &plus));
+        assert_eq!(46, eval("2 * 3 + (4 * 5)", &plus));
+        assert_eq!(
+            669060,
+            eval("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", &plus)
+        );
+        assert_eq!(
+            23340,
+            eval("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", &plus)
+        );
+    }
+
+    #[test]
+    fn test_multi_digit_and_errors() {
+        assert_eq!(2234, eval("1000 + 17 * 2 + 200", &Precedence::flat()));
+        assert_eq!(1234, eval("1000 + 17 * 2 + 200", &Precedence::arithmetic()));
+
+        let parse_line = |line| parse(&tokenize(line).unwrap(), &Precedence::flat());
+        let error = |kind, start, end| Err(ExprError::new(kind, Span { start, end }));
+        let expected = |expected, found| ErrorKind::Unexpected { expected, found };
+        assert_eq!(
+            error(expected(Expected::ClosingParen, None), 6, 7),
+            parse_line("(1 + 2")
+        );
+        assert_eq!(
+            error(expected(Expected::Operand, None), 3, 4),
+            parse_line("1 +")
+        );
+        assert_eq!(
+            error(expected(Expected::Operand, None), 0, 1),
+            parse_line("")
+        );
+        assert_eq!(
+            error(
+                expected(Expected::OperatorOrEnd, Some(TokenKind::RParen)),
+                5,
+                6
+            ),
+            parse_line("1 + 2)")
+        );
+        assert_eq!(
+            error(expected(Expected::Operand, Some(TokenKind::Star)), 4, 5),
+            parse_line("1 + * 2")
+        );
+        assert_eq!(
+            error(
+                expected(Expected::ClosingParen, Some(TokenKind::Number(2))),
+                3,
+                4
+            ),
+            parse_line("(1 2) 3")
+        );
+
+        let additions = Precedence::new().with(BinOp::Add, 1, Assoc::Left);
+        assert_eq!(
+            error(ErrorKind::Unsupported('*'), 2, 3),
+            parse(&tokenize("1 * 2").unwrap(), &additions)
+        );
+        assert_eq!(
+            error(ErrorKind::Unsupported('-'), 0, 1),
+            parse(&tokenize("-1").unwrap(), &additions)
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let maths = Precedence::arithmetic();
+        assert_eq!(-4, eval("-2 ^ 2", &maths));
+        assert_eq!(4, eval("(-2) ^ 2", &maths));
+        assert_eq!(2, eval("2 ^ 3 ^ 0", &maths));
+        assert_eq!(3, eval("10 - 4 - 3", &maths));
+        assert_eq!(5, eval("100 / 10 / 2", &maths));
+        assert_eq!(-3, eval("-7 / 2", &maths));
+        assert_eq!(14, eval("2 + 3 * 4", &maths));
+        assert_eq!(6, eval("2 * --3", &maths));
+
+        // The same operators through the puzzle modes.
+        assert_eq!(12, eval("10 - 4 - 3 * 4", &Precedence::flat()));
+        assert_eq!(20, eval("2 + 3 * 4", &Precedence::flat()));
+        assert_eq!(36, eval("-2 ^ 2 * 9", &Precedence::flat()));
+        assert_eq!(20, eval("2 + 3 * 4", &Precedence::addition_first()));
+        assert_eq!(3, eval("10 - 4 - 3", &Precedence::addition_first()));
+
+        let right = Precedence::new()
+            .with(BinOp::Sub, 1, Assoc::Right)
+            .with_negation(2);
+        assert_eq!(9, eval("10 - 4 - 3", &right));
+
+        let tightest = Precedence::new()
+            .with(BinOp::Sub, 255, Assoc::Left)
+            .with(BinOp::Add, 0, Assoc::Left)
+            .with_negation(255);
+        assert_eq!(3, eval("10 - 4 - 3", &tightest));
+        // Negation at the same level takes the whole subtraction.
+        assert_eq!(0, eval("-1 - 1 + 0", &tightest));
+    }
+
+    #[test]
+    fn test_arithmetic_errors() {
+        let check = |line| {
+            parse(&tokenize(line).unwrap(), &Precedence::arithmetic())
+                .unwrap()
+                .eval()
+        };
+        let error = |kind, start| {
+            Err(ExprError::new(
+                kind,
+                Span {
+                    start,
+                    end: start + 1,
+                },
+            ))
+        };
+        assert_eq!(error(ErrorKind::DivisionByZero, 2), check("1 / (2 - 2)"));
+        assert_eq!(error(ErrorKind::NegativeExponent, 2), check("2 ^ -1"));
+        assert_eq!(error(ErrorKind::Overflow, 2), check("2 ^ 63"));
+        assert_eq!(error(ErrorKind::Overflow, 3), check("-2 ^ 63"));
+        assert_eq!(Ok(i64::MIN), check("-9223372036854775807 - 1"));
+        assert_eq!(
+            error(ErrorKind::Overflow, 0),
+            check("-(-9223372036854775807 - 1)")
+        );
+        assert_eq!(
+            error(ErrorKind::Overflow, 20),
+            check("9223372036854775807 + 1")
+        );
+        assert_eq!(error(ErrorKind::Overflow, 2), check("2 ^ 4294967296"));
+        assert_eq!(
+            error(ErrorKind::Overflow, 11),
+            check("3037000500 * 3037000500")
+        );
+        assert_eq!(
+            error(ErrorKind::Overflow, 27),
+            check("(-9223372036854775807 - 1) / -1")
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use super::{Expr, ExprError};
+use std::fmt::{self, Display, Formatter};
+
+// Every intermediate expression of an evaluation, reducing one operation at a
+// time in the order `Expr::eval` does them, and how it ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<Expr>,
+    pub result: Result<i64, ExprError>,
+}
+
+impl Trace {
+    pub fn new(expr: &Expr) -> Self {
+        let mut current = expr.clone();
+        let mut steps = vec![current.clone()];
+        let result = loop {
+            match reduce(&mut current) {
+                Ok(true) => steps.push(current.clone()),
+                Ok(false) => break current.eval(),
+                Err(e) => break Err(e),
+            }
+        };
+        Trace { steps, result }
+    }
+}
+
+// Numbers and negated number literals like `-1`, which are left alone so a
+// negative operand isn't reported as a step of its own.
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number(_) => true,
+        Expr::Neg { operand, .. } => matches!(**operand, Expr::Number(n) if n >= 0),
+        _ => false,
+    }
+}
+
+// Replaces the leftmost operation whose operands are both literals by its
+// value, returns false once only a literal is left.
+fn reduce(expr: &mut Expr) -> Result<bool, ExprError> {
+    if is_literal(expr) {
+        return Ok(false);
+    }
+    match expr {
+        Expr::Neg { operand, .. } if !is_literal(operand) => return reduce(operand),
+        Expr::Binary { lhs, .. } if !is_literal(lhs) => return reduce(lhs),
+        Expr::Binary { rhs, .. } if !is_literal(rhs) => return reduce(rhs),
+        _ => {}
+    }
+    *expr = Expr::Number(expr.eval()?);
+    Ok(true)
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            let prefix = if i == 0 { "" } else { "= " };
+            writeln!(f, "{}{}", prefix, step)?;
+        }
+        if let Err(e) = &self.result {
+            writeln!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{parse, tokenize, Precedence};
+
+    fn trace(line: &str, precedence: &Precedence) -> Trace {
+        Trace::new(&parse(&tokenize(line).unwrap(), precedence).unwrap())
+    }
+
+    #[test]
+    fn test_trace() {
+        let flat = trace("1 + 2 * 3 + 4 * 5 + 6", &Precedence::flat());
+        assert_eq!(Ok(71), flat.result);
+        assert_eq!(
+            "((((1 + 2) * 3) + 4) * 5) + 6\n\
+             = (((3 * 3) + 4) * 5) + 6\n\
+             = ((9 + 4) * 5) + 6\n\
+             = (13 * 5) + 6\n\
+             = 65 + 6\n\
+             = 71\n",
+            flat.to_string()
+        );
+
+        let plus = trace("1 + 2 * 3 + 4 * 5 + 6", &Precedence::addition_first());
+        assert_eq!(Ok(231), plus.result);
+        let steps: Vec<String> = plus.steps.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            vec![
+                "((1 + 2) * (3 + 4)) * (5 + 6)",
+                "(3 * (3 + 4)) * (5 + 6)",
+                "(3 * 7) * (5 + 6)",
+                "21 * (5 + 6)",
+                "21 * 11",
+                "231"
+            ],
+            steps
+        );
+
+        let negated = trace("-(2 - 5) * -1", &Precedence::arithmetic());
+        assert_eq!(Ok(-3), negated.result);
+        assert_eq!(
+            "(-(2 - 5)) * (-1)\n= (-(-3)) * (-1)\n= 3 * (-1)\n= -3\n",
+            negated.to_string()
+        );
+    }
+
+    #[test]
+    fn test_trace_error() {
+        let failed = trace("(1 + 1) * (3 / (2 - 2))", &Precedence::arithmetic());
+        assert_eq!(3, failed.steps.len());
+        assert!(failed
+            .to_string()
+            .ends_with("= 2 * (3 / 0)\ncolumn 14: division by zero\n"));
+    }
+}
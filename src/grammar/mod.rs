@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+mod parser;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Symbol {
+    Rule(usize),
+    // Never empty, so every symbol consumes at least one character.
+    Literal(String),
+}
+
+// Alternatives separated by `|`, each one a sequence of symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub alternatives: Vec<Vec<Symbol>>,
+}
+
+// Numbered rules like `0: 4 1 5`, `1: 2 3 | 3 2` and `4: "a"`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Grammar {
+    rules: HashMap<usize, Rule>,
+}
+
+impl Grammar {
+    // Whether all of `message` derives from rule `start`.
+    pub fn matches(&self, start: usize, message: &str) -> bool {
+        let mut rests = Vec::new();
+        self.match_symbol(&Symbol::Rule(start), message, &mut rests);
+        rests.iter().any(|rest| rest.is_empty())
+    }
+
+    // Adds every way of matching a prefix of `unparsed` to `rests`, as what's
+    // left after it. Nothing matches the empty string, which is what stops
+    // right recursive rules like `8: 42 | 42 8`. Left recursive ones are
+    // rejected by the parser.
+    fn match_symbol<'a>(&self, symbol: &Symbol, unparsed: &'a str, rests: &mut Vec<&'a str>) {
+        if unparsed.is_empty() {
+            return;
+        }
+        match symbol {
+            Symbol::Literal(s) => {
+                if let Some(rest) = unparsed.strip_prefix(s.as_str()) {
+                    add(rests, rest);
+                }
+            }
+            Symbol::Rule(id) => {
+                if let Some(rule) = self.rules.get(id) {
+                    for sequence in &rule.alternatives {
+                        self.match_sequence(sequence, unparsed, rests);
+                    }
+                }
+            }
+        }
+    }
+
+    // Goes through the sequence one symbol at a time for all rests at once, so
+    // ambiguous symbols don't multiply the work for the ones after them.
+    fn match_sequence<'a>(&self, sequence: &[Symbol], unparsed: &'a str, rests: &mut Vec<&'a str>) {
+        let (last, init) = match sequence.split_last() {
+            Some(split) => split,
+            None => return,
+        };
+        if init.is_empty() {
+            return self.match_symbol(last, unparsed, rests);
+        }
+        let mut current = vec![unparsed];
+        let mut next = Vec::new();
+        for symbol in init {
+            for rest in &current {
+                self.match_symbol(symbol, rest, &mut next);
+            }
+            if next.is_empty() {
+                return;
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        for rest in current {
+            self.match_symbol(last, rest, rests);
+        }
+    }
+}
+
+// Rests are all suffixes of the same message, so their lengths tell them
+// apart. Without this, ambiguous rules match the same rest over and over.
+fn add<'a>(rests: &mut Vec<&'a str>, rest: &'a str) {
+    if rests.iter().all(|r| r.len() != rest.len()) {
+        rests.push(rest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "0: 4 1 5\n1: 2 3 | 3 2\n2: 4 4 | 5 5\n3: 4 5 | 5 4\n4: \"a\"\n5: \"b\"";
+
+    #[test]
+    fn test_matches() {
+        let grammar: Grammar = EXAMPLE.parse().unwrap();
+        let matching: Vec<&str> = ["ababbb", "bababa", "abbbab", "aaabbb", "aaaabbb"]
+            .iter()
+            .copied()
+            .filter(|m| grammar.matches(0, m))
+            .collect();
+        assert_eq!(vec!["ababbb", "abbbab"], matching);
+        assert!(!grammar.matches(7, "a"));
+    }
+
+    #[test]
+    fn test_long_sequences_and_literals() {
+        let grammar: Grammar =
+            "0: 1 1 1 1 | 2 \"x\" 2 2 2\n1: \"ab\"\n2: \"c\" | \"d\" | \"e\" | \"f\""
+                .parse()
+                .unwrap();
+        assert!(grammar.matches(0, "abababab"));
+        assert!(!grammar.matches(0, "ababab"));
+        assert!(grammar.matches(0, "cxdef"));
+        assert!(!grammar.matches(0, "cxdeg"));
+    }
+
+    #[test]
+    fn test_recursive_rules() {
+        let grammar: Grammar = "0: 8 11\n8: 42 | 42 8\n11: 42 31 | 42 11 31\n42: \"a\"\n31: \"b\""
+            .parse()
+            .unwrap();
+        assert!(grammar.matches(0, "aab"));
+        assert!(grammar.matches(0, "aaaabb"));
+        assert!(!grammar.matches(0, "aabb"));
+        assert!(!grammar.matches(0, "abb"));
+    }
+
+    #[test]
+    fn test_ambiguous_rules() {
+        // Each of the 24 steps can take one or two a's, that's 2^24 ways to go
+        // but never more than 25 different rests.
+        let source = format!("0:{}\n1: 2 | 2 2\n2: \"a\"", " 1".repeat(24));
+        let grammar: Grammar = source.parse().unwrap();
+        assert!(grammar.matches(0, &"a".repeat(36)));
+        assert!(!grammar.matches(0, &"a".repeat(49)));
+        let mut rests = Vec::new();
+        grammar.match_symbol(&Symbol::Rule(1), "aa", &mut rests);
+        grammar.match_symbol(&Symbol::Rule(2), "aa", &mut rests);
+        assert_eq!(vec!["a", ""], rests);
+    }
+}
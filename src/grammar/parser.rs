@@ -0,0 +1,236 @@
+use super::{Grammar, Rule, Symbol};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    MissingColon,
+    BadRuleId(String),
+    DuplicateRule(usize),
+    EmptyAlternative,
+    BadSymbol(String),
+    UnterminatedLiteral,
+    EmptyLiteral,
+    UnknownRule(usize),
+    LeftRecursion(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrammarError {
+    // Counted from 1 including blank lines, so it points into the source text.
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ErrorKind::MissingColon => write!(f, "expected `<id>: <rule>`"),
+            ErrorKind::BadRuleId(id) => write!(f, "bad rule id {:?}", id),
+            ErrorKind::DuplicateRule(id) => write!(f, "rule {} defined twice", id),
+            ErrorKind::EmptyAlternative => write!(f, "empty alternative"),
+            ErrorKind::BadSymbol(s) => write!(f, "bad symbol {:?}", s),
+            ErrorKind::UnterminatedLiteral => write!(f, "unterminated string"),
+            ErrorKind::EmptyLiteral => write!(f, "empty string"),
+            ErrorKind::UnknownRule(id) => write!(f, "unknown rule {}", id),
+            ErrorKind::LeftRecursion(id) => write!(f, "rule {} is left recursive", id),
+        }
+    }
+}
+
+// Splits a rule body into its alternatives. Literals are quoted and may hold
+// spaces or `|`, everything else is a rule number.
+fn parse_body(body: &str) -> Result<Rule, ErrorKind> {
+    let mut alternatives = vec![Vec::new()];
+    let mut rest = body.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if c == '|' {
+            alternatives.push(Vec::new());
+            rest = rest[1..].trim_start();
+            continue;
+        }
+        let (symbol, tail) = if c == '"' {
+            let end = rest[1..].find('"').ok_or(ErrorKind::UnterminatedLiteral)? + 1;
+            if end == 1 {
+                return Err(ErrorKind::EmptyLiteral);
+            }
+            (Symbol::Literal(rest[1..end].to_string()), &rest[end + 1..])
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '|' || c == '"')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            let id = word
+                .parse()
+                .map_err(|_| ErrorKind::BadSymbol(word.to_string()))?;
+            (Symbol::Rule(id), &rest[end..])
+        };
+        alternatives.last_mut().unwrap().push(symbol);
+        rest = tail.trim_start();
+    }
+
+    if alternatives.iter().any(|sequence| sequence.is_empty()) {
+        return Err(ErrorKind::EmptyAlternative);
+    }
+    Ok(Rule { alternatives })
+}
+
+// Whether rule `id` can get back to itself without consuming anything, that
+// is through the first symbol of its alternatives. Matching such a rule would
+// recurse forever.
+fn left_recursive(rules: &HashMap<usize, Rule>, id: usize) -> bool {
+    let mut seen = HashSet::new();
+    let mut todo = vec![id];
+    while let Some(current) = todo.pop() {
+        for sequence in &rules[&current].alternatives {
+            if let Some(Symbol::Rule(first)) = sequence.first() {
+                if *first == id {
+                    return true;
+                }
+                if seen.insert(*first) {
+                    todo.push(*first);
+                }
+            }
+        }
+    }
+    false
+}
+
+// One `id: rule` per line, blank lines are skipped. Every rule referenced has
+// to be defined somewhere in the source.
+impl FromStr for Grammar {
+    type Err = GrammarError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut rules = HashMap::new();
+        let mut lines: HashMap<usize, usize> = HashMap::new();
+
+        for (i, raw) in source.lines().enumerate() {
+            let line = i + 1;
+            let error = |kind| GrammarError { line, kind };
+            if raw.trim().is_empty() {
+                continue;
+            }
+
+            let colon = raw
+                .find(':')
+                .ok_or_else(|| error(ErrorKind::MissingColon))?;
+            let id_str = raw[..colon].trim();
+            let id = id_str
+                .parse()
+                .map_err(|_| error(ErrorKind::BadRuleId(id_str.to_string())))?;
+            let rule = parse_body(&raw[colon + 1..]).map_err(error)?;
+            if rules.insert(id, rule).is_some() {
+                return Err(error(ErrorKind::DuplicateRule(id)));
+            }
+            lines.insert(id, line);
+        }
+
+        let mut ids: Vec<&usize> = rules.keys().collect();
+        ids.sort_unstable_by_key(|id| lines[id]);
+        for &id in &ids {
+            let unknown = rules[id]
+                .alternatives
+                .iter()
+                .flatten()
+                .find_map(|s| match s {
+                    Symbol::Rule(r) if !rules.contains_key(r) => Some(*r),
+                    _ => None,
+                });
+            if let Some(r) = unknown {
+                return Err(GrammarError {
+                    line: lines[id],
+                    kind: ErrorKind::UnknownRule(r),
+                });
+            }
+        }
+        for &id in &ids {
+            if left_recursive(&rules, *id) {
+                return Err(GrammarError {
+                    line: lines[id],
+                    kind: ErrorKind::LeftRecursion(*id),
+                });
+            }
+        }
+
+        Ok(Grammar { rules })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Symbol::*;
+
+    #[test]
+    fn test_parse() {
+        let grammar: Grammar = "0: 1 2 | 2\n\n1: \"a b\" | \"|\"\n2: \"c\"\"d\" 1"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            vec![vec![Rule(1), Rule(2)], vec![Rule(2)]],
+            grammar.rules[&0].alternatives
+        );
+        assert_eq!(
+            vec![vec![Literal("a b".into())], vec![Literal("|".into())]],
+            grammar.rules[&1].alternatives
+        );
+        assert_eq!(
+            vec![vec![Literal("c".into()), Literal("d".into()), Rule(1)]],
+            grammar.rules[&2].alternatives
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |line, kind| Err(GrammarError { line, kind });
+        let parse = |s: &str| s.parse::<Grammar>();
+
+        assert_eq!(
+            error(2, ErrorKind::MissingColon),
+            parse("0: \"a\"\n1 \"b\"")
+        );
+        assert_eq!(
+            error(1, ErrorKind::BadRuleId("x".into())),
+            parse("x: \"a\"")
+        );
+        assert_eq!(
+            error(2, ErrorKind::DuplicateRule(0)),
+            parse("0: \"a\"\n0: \"b\"")
+        );
+        assert_eq!(error(1, ErrorKind::EmptyAlternative), parse("0: \"a\" |"));
+        assert_eq!(error(1, ErrorKind::EmptyAlternative), parse("0:"));
+        assert_eq!(
+            error(1, ErrorKind::BadSymbol("a".into())),
+            parse("0: 1 a\n1: \"a\"")
+        );
+        assert_eq!(error(1, ErrorKind::UnterminatedLiteral), parse("0: \"a"));
+        assert_eq!(error(1, ErrorKind::EmptyLiteral), parse("0: \"\""));
+        assert_eq!(
+            error(2, ErrorKind::UnknownRule(3)),
+            parse("0: 1\n1: 2 | 3\n2: \"a\"")
+        );
+        assert_eq!(
+            "line 2: unknown rule 3",
+            parse("0: 1\n1: 2 | 3\n2: \"a\"").unwrap_err().to_string()
+        );
+        assert_eq!(
+            error(1, ErrorKind::LeftRecursion(0)),
+            parse("0: 0 1 | 1\n1: \"a\"")
+        );
+        assert_eq!(
+            error(2, ErrorKind::LeftRecursion(1)),
+            parse("0: \"a\" 1\n1: 2 \"b\"\n2: \"c\" | 3\n3: 1 \"d\"")
+        );
+        assert_eq!(
+            "line 1: rule 0 is left recursive",
+            parse("0: 0 1 | 1\n1: \"a\"").unwrap_err().to_string()
+        );
+        assert!(parse("0: 1 0 | 1\n1: \"a\"").is_ok());
+    }
+}
@@ -1,11 +1,14 @@
 use core::fmt;
 use std::{
+    collections::HashMap,
     fmt::{Display, Formatter},
     io::{BufRead, BufReader, Read},
+    mem,
     str::FromStr,
 };
 
 extern crate itertools;
+use itertools::iproduct;
 
 pub trait Coord {
     fn x(&self) -> usize;
@@ -56,6 +59,14 @@ const DIRS: [(i64, i64); 8] = [
     (1, 1),
 ];
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Grid {
     cells: Vec<char>,
@@ -73,6 +84,22 @@ impl Grid {
         Ok(Grid { cells, h, w })
     }
 
+    pub fn new(w: usize, h: usize, fill: char) -> Self {
+        Grid {
+            cells: vec![fill; w * h],
+            w,
+            h,
+        }
+    }
+
+    fn index_of(&self, c: impl Coord) -> usize {
+        c.x() + c.y() * self.w
+    }
+
+    fn coords_of(&self, i: usize) -> (usize, usize) {
+        (i % self.w, i / self.w)
+    }
+
     pub fn get(&self, c: impl Coord) -> Option<&char> {
         if c.x() < self.w && c.y() < self.h {
             self.cells.get(c.x() + c.y() * self.w)
@@ -91,6 +118,20 @@ impl Grid {
         self.cells.iter().cloned().filter(|&x| c == x).count()
     }
 
+    pub fn adjacent_coords(&self, c: impl Coord) -> Vec<(usize, usize)> {
+        let mut result = Vec::with_capacity(8);
+
+        for y in c.y().saturating_sub(1)..=(c.y() + 1).min(self.h - 1) {
+            for x in c.x().saturating_sub(1)..=(c.x() + 1).min(self.w - 1) {
+                if x != c.x() || y != c.y() {
+                    result.push((x, y));
+                }
+            }
+        }
+
+        result
+    }
+
     pub fn count_adjacents(&self, c: impl Coord, target: char) -> usize {
         let mut result: Vec<char> = Vec::new();
 
@@ -111,6 +152,8 @@ impl Grid {
         result.len()
     }
 
+    // The direct scans `sight_table` replaced, kept to check the table against.
+    #[cfg(test)]
     pub fn get_first_in_line(&self, c: &impl Coord, line: (i64, i64)) -> Option<char> {
         let (mut x, mut y) = (c.x() as i64, c.y() as i64);
         loop {
@@ -128,12 +171,455 @@ impl Grid {
         None
     }
 
+    #[cfg(test)]
     pub fn count_line_of_sight(&self, c: &impl Coord, target: char) -> usize {
         DIRS.iter()
             .filter_map(|&(x, y)| self.get_first_in_line(c, (x, y)))
             .filter(|&v| v == target)
             .count()
     }
+
+    // Floor cells never block sight, so the table stays valid for as long as
+    // the floor layout does, no matter how the other cells change.
+    pub fn sight_table(&self) -> SightTable {
+        let mut visible = vec![[None; 8]; self.cells.len()];
+
+        for (d, &(dx, dy)) in DIRS.iter().enumerate() {
+            let ys: Vec<usize> = if dy > 0 {
+                (0..self.h).rev().collect()
+            } else {
+                (0..self.h).collect()
+            };
+            let xs: Vec<usize> = if dx > 0 {
+                (0..self.w).rev().collect()
+            } else {
+                (0..self.w).collect()
+            };
+
+            for &y in ys.iter() {
+                for &x in xs.iter() {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx as usize >= self.w || ny as usize >= self.h {
+                        continue;
+                    }
+                    let next = self.index_of((nx as usize, ny as usize));
+                    visible[self.index_of((x, y))][d] = match self.cells[next] {
+                        '.' => visible[next][d],
+                        _ => Some(next),
+                    };
+                }
+            }
+        }
+
+        SightTable { w: self.w, visible }
+    }
+
+    pub fn count_visible(&self, table: &SightTable, c: impl Coord, target: char) -> usize {
+        table.visible[self.index_of(c)]
+            .iter()
+            .filter_map(|&i| i)
+            .filter(|&i| self.cells[i] == target)
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SightTable {
+    w: usize,
+    visible: Vec<[Option<usize>; 8]>,
+}
+
+impl SightTable {
+    pub fn visible_coords(&self, c: impl Coord) -> Vec<(usize, usize)> {
+        self.visible[c.x() + c.y() * self.w]
+            .iter()
+            .filter_map(|&i| i)
+            .map(|i| (i % self.w, i / self.w))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    FixedPoint { generation: usize },
+    Cycle { start: usize, period: usize },
+    StepLimit { generation: usize },
+}
+
+// Steps a grid synchronously: every cell of generation n + 1 is computed from
+// generation n, using two buffers that get swapped instead of cloning.
+pub struct Simulation<F> {
+    grid: Grid,
+    buffer: Grid,
+    rule: F,
+    generation: usize,
+    dependents: Option<Vec<Vec<usize>>>,
+    pending: Vec<usize>,
+    history: Option<HashMap<Vec<char>, usize>>,
+}
+
+impl<F: Fn(&Grid, (usize, usize), char) -> char> Simulation<F> {
+    pub fn new(grid: Grid, rule: F) -> Self {
+        let pending = (0..grid.cells.len()).collect();
+        Simulation {
+            buffer: grid.clone(),
+            grid,
+            rule,
+            generation: 0,
+            dependents: None,
+            pending,
+            history: None,
+        }
+    }
+
+    // Only re-evaluate cells whose neighbourhood changed in the last step.
+    // `neighbours` must return every cell the rule reads for a given cell.
+    pub fn track_changes<N>(mut self, neighbours: N) -> Self
+    where
+        N: Fn(&Grid, (usize, usize)) -> Vec<(usize, usize)>,
+    {
+        let mut dependents = vec![Vec::new(); self.grid.cells.len()];
+        for i in 0..self.grid.cells.len() {
+            let c = self.grid.coords_of(i);
+            for n in neighbours(&self.grid, c) {
+                dependents[self.grid.index_of(n)].push(i);
+            }
+        }
+        self.dependents = Some(dependents);
+        self
+    }
+
+    pub fn detect_cycles(mut self) -> Self {
+        let mut history = HashMap::new();
+        history.insert(self.grid.cells.clone(), self.generation);
+        self.history = Some(history);
+        self
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid {
+        self.grid
+    }
+
+    // Advances one generation, returning the indices of the cells that changed.
+    // A step that changes nothing doesn't count as a generation.
+    pub fn step(&mut self) -> Vec<usize> {
+        let mut changed = Vec::new();
+        for &i in self.pending.iter() {
+            let current = self.grid.cells[i];
+            let new = (self.rule)(&self.grid, self.grid.coords_of(i), current);
+            if new != current {
+                self.buffer.cells[i] = new;
+                changed.push(i);
+            }
+        }
+
+        if changed.is_empty() {
+            return changed;
+        }
+        mem::swap(&mut self.grid, &mut self.buffer);
+        for &i in changed.iter() {
+            self.buffer.cells[i] = self.grid.cells[i];
+        }
+        self.generation += 1;
+
+        if let Some(dependents) = &self.dependents {
+            let mut dirty = vec![false; self.grid.cells.len()];
+            for &i in changed.iter() {
+                dirty[i] = true;
+                for &d in dependents[i].iter() {
+                    dirty[d] = true;
+                }
+            }
+            self.pending = (0..dirty.len()).filter(|&i| dirty[i]).collect();
+        }
+
+        changed
+    }
+
+    pub fn run(&mut self) -> Outcome {
+        self.run_for(usize::MAX)
+    }
+
+    pub fn run_for(&mut self, max_steps: usize) -> Outcome {
+        for _ in 0..max_steps {
+            if self.step().is_empty() {
+                return Outcome::FixedPoint {
+                    generation: self.generation,
+                };
+            }
+            if let Some(history) = self.history.as_mut() {
+                if let Some(&start) = history.get(&self.grid.cells) {
+                    return Outcome::Cycle {
+                        start,
+                        period: self.generation - start,
+                    };
+                }
+                history.insert(self.grid.cells.clone(), self.generation);
+            }
+        }
+
+        Outcome::StepLimit {
+            generation: self.generation,
+        }
+    }
+}
+
+// Iteration: coordinates are always yielded as (x, y), row by row.
+impl Grid {
+    pub fn row(&self, y: usize) -> &[char] {
+        &self.cells[y * self.w..(y + 1) * self.w]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[char]> {
+        (0..self.h).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &char> {
+        assert!(x < self.w, "column {} out of bounds, width {}", x, self.w);
+        self.cells[x..].iter().step_by(self.w)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &char>> {
+        (0..self.w).map(move |x| self.column(x))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &char)> {
+        let w = self.w;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, c)| ((i % w, i / w), c))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut char)> {
+        let w = self.w;
+        self.cells
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, c)| ((i % w, i / w), c))
+    }
+
+    pub fn positions(&self, target: char) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.iter()
+            .filter(move |&(_, &c)| c == target)
+            .map(|(coord, _)| coord)
+    }
+
+    pub fn find(&self, target: char) -> Option<(usize, usize)> {
+        self.positions(target).next()
+    }
+
+    pub fn map<F: Fn((usize, usize), char) -> char>(&self, f: F) -> Grid {
+        Grid {
+            cells: self.iter().map(|(coord, &c)| f(coord, c)).collect(),
+            w: self.w,
+            h: self.h,
+        }
+    }
+}
+
+// Geometry: every transformation returns a new grid, the original is untouched.
+impl Grid {
+    fn from_fn<F: Fn(usize, usize) -> char>(w: usize, h: usize, f: F) -> Self {
+        let cells = (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Grid { cells, w, h }
+    }
+
+    fn at(&self, x: usize, y: usize) -> char {
+        self.cells[x + y * self.w]
+    }
+
+    pub fn transpose(&self) -> Grid {
+        Grid {
+            cells: self.columns().flatten().copied().collect(),
+            w: self.h,
+            h: self.w,
+        }
+    }
+
+    pub fn flip_horizontal(&self) -> Grid {
+        self.map(|(x, y), _| self.at(self.w - 1 - x, y))
+    }
+
+    pub fn flip_vertical(&self) -> Grid {
+        self.map(|(x, y), _| self.at(x, self.h - 1 - y))
+    }
+
+    // Clockwise, in steps of 90 degrees.
+    pub fn rotate(&self, quarter_turns: usize) -> Grid {
+        match quarter_turns % 4 {
+            0 => self.clone(),
+            1 => Grid::from_fn(self.h, self.w, |x, y| self.at(y, self.h - 1 - x)),
+            2 => Grid::from_fn(self.w, self.h, |x, y| {
+                self.at(self.w - 1 - x, self.h - 1 - y)
+            }),
+            _ => Grid::from_fn(self.h, self.w, |x, y| self.at(self.w - 1 - y, x)),
+        }
+    }
+
+    // Top and bottom are read left to right, left and right top to bottom.
+    pub fn border(&self, side: Side) -> Vec<char> {
+        match side {
+            Side::Top => self.row(0).to_vec(),
+            Side::Bottom => self.row(self.h - 1).to_vec(),
+            Side::Left => self.column(0).cloned().collect(),
+            Side::Right => self.column(self.w - 1).cloned().collect(),
+        }
+    }
+
+    pub fn borders(&self) -> [Vec<char>; 4] {
+        [
+            self.border(Side::Top),
+            self.border(Side::Right),
+            self.border(Side::Bottom),
+            self.border(Side::Left),
+        ]
+    }
+
+    // Panics if the requested area does not fit inside the grid.
+    pub fn view(&self, origin: impl Coord, w: usize, h: usize) -> GridView<'_> {
+        assert!(origin.x() + w <= self.w && origin.y() + h <= self.h);
+        GridView {
+            grid: self,
+            x: origin.x(),
+            y: origin.y(),
+            w,
+            h,
+        }
+    }
+
+    pub fn crop(&self, origin: impl Coord, w: usize, h: usize) -> Grid {
+        self.view(origin, w, h).to_grid()
+    }
+}
+
+// Flipping is applied before rotating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orientation {
+    pub quarter_turns: usize,
+    pub flipped: bool,
+}
+
+impl Orientation {
+    pub fn all() -> Vec<Orientation> {
+        iproduct!(&[false, true], 0..4)
+            .map(|(&flipped, quarter_turns)| Orientation {
+                quarter_turns,
+                flipped,
+            })
+            .collect()
+    }
+
+    pub fn apply(&self, grid: &Grid) -> Grid {
+        if self.flipped {
+            grid.flip_horizontal().rotate(self.quarter_turns)
+        } else {
+            grid.rotate(self.quarter_turns)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternMatch {
+    pub orientation: Orientation,
+    pub position: (usize, usize),
+}
+
+// Pattern search: cells of the pattern equal to `wildcard` match anything.
+impl Grid {
+    pub fn matches_at(&self, pattern: &Grid, origin: impl Coord, wildcard: char) -> bool {
+        if origin.x() + pattern.w > self.w || origin.y() + pattern.h > self.h {
+            return false;
+        }
+        iproduct!(0..pattern.h, 0..pattern.w).all(|(y, x)| {
+            let expected = pattern.at(x, y);
+            expected == wildcard || expected == self.at(origin.x() + x, origin.y() + y)
+        })
+    }
+
+    pub fn find_pattern(&self, pattern: &Grid, wildcard: char) -> Vec<(usize, usize)> {
+        if pattern.w > self.w || pattern.h > self.h {
+            return vec![];
+        }
+        iproduct!(0..=self.h - pattern.h, 0..=self.w - pattern.w)
+            .map(|(y, x)| (x, y))
+            .filter(|&origin| self.matches_at(pattern, origin, wildcard))
+            .collect()
+    }
+
+    // Symmetric patterns are only searched once per distinct orientation.
+    pub fn find_pattern_oriented(&self, pattern: &Grid, wildcard: char) -> Vec<PatternMatch> {
+        let mut seen: Vec<Grid> = Vec::new();
+        let mut result = Vec::new();
+
+        for orientation in Orientation::all() {
+            let oriented = orientation.apply(pattern);
+            if seen.contains(&oriented) {
+                continue;
+            }
+            result.extend(
+                self.find_pattern(&oriented, wildcard)
+                    .into_iter()
+                    .map(|position| PatternMatch {
+                        orientation,
+                        position,
+                    }),
+            );
+            seen.push(oriented);
+        }
+
+        result
+    }
+
+    pub fn mark_pattern(
+        &mut self,
+        pattern: &Grid,
+        found: &PatternMatch,
+        wildcard: char,
+        mark: char,
+    ) {
+        let oriented = found.orientation.apply(pattern);
+        let (ox, oy) = found.position;
+        for (y, x) in iproduct!(0..oriented.h, 0..oriented.w) {
+            if oriented.at(x, y) != wildcard {
+                self.set((ox + x, oy + y), mark);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GridView<'a> {
+    grid: &'a Grid,
+    x: usize,
+    y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl<'a> GridView<'a> {
+    pub fn get(&self, c: impl Coord) -> Option<&'a char> {
+        if c.x() < self.w && c.y() < self.h {
+            self.grid.get((self.x + c.x(), self.y + c.y()))
+        } else {
+            None
+        }
+    }
+
+    pub fn to_grid(self) -> Grid {
+        Grid::from_fn(self.w, self.h, |x, y| *self.get((x, y)).unwrap())
+    }
 }
 
 impl FromStr for Grid {
@@ -146,7 +632,7 @@ impl FromStr for Grid {
 
 impl Display for Grid {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
-        for row in self.cells.chunks(self.w) {
+        for row in self.rows() {
             for cell in row {
                 write!(f, "{}", cell)?;
             }
@@ -188,6 +674,189 @@ mod tests {
         assert_eq!(3, grid.count_adjacents((1, 1), '#'));
     }
 
+    #[test]
+    fn test_sight_table() {
+        let grid: Grid = ".......#.\n\
+            ...#.....\n\
+            .#.......\n\
+            .........\n\
+            ..#L....#\n\
+            ....#....\n\
+            .........\n\
+            #........\n\
+            ...#....."
+            .parse()
+            .unwrap();
+        let table = grid.sight_table();
+
+        assert_eq!(8, grid.count_visible(&table, (3, 4), '#'));
+        for (coord, _) in grid.iter() {
+            assert_eq!(
+                grid.count_line_of_sight(&coord, '#'),
+                grid.count_visible(&table, coord, '#')
+            );
+        }
+        assert_eq!(vec![(0, 7), (7, 0)], table.visible_coords((0, 0)));
+    }
+
+    #[test]
+    fn test_simulation() {
+        let blinker: Grid = ".....\n..#..\n..#..\n..#..\n.....".parse().unwrap();
+        let life =
+            |g: &Grid, c: (usize, usize), current: char| match (current, g.count_adjacents(c, '#'))
+            {
+                ('#', 2) | (_, 3) => '#',
+                _ => '.',
+            };
+
+        let mut sim = Simulation::new(blinker.clone(), life).detect_cycles();
+        assert_eq!(
+            Outcome::Cycle {
+                start: 0,
+                period: 2
+            },
+            sim.run()
+        );
+        assert_eq!(2, sim.generation());
+        assert_eq!(&blinker, sim.grid());
+
+        let mut sim = Simulation::new(blinker.clone(), life).track_changes(Grid::adjacent_coords);
+        sim.step();
+        assert_eq!(
+            ".....\n.....\n.###.\n.....\n.....\n",
+            sim.grid().to_string()
+        );
+        assert_eq!(Outcome::StepLimit { generation: 5 }, sim.run_for(4));
+
+        let block: Grid = "....\n.##.\n.##.\n....".parse().unwrap();
+        let mut sim = Simulation::new(block.clone(), life).track_changes(Grid::adjacent_coords);
+        assert_eq!(Outcome::FixedPoint { generation: 0 }, sim.run());
+        assert_eq!(0, sim.generation());
+        assert_eq!(block, sim.into_grid());
+
+        let lonely: Grid = "...\n.#.\n...".parse().unwrap();
+        let mut sim = Simulation::new(lonely, life);
+        assert_eq!(Outcome::FixedPoint { generation: 1 }, sim.run());
+        assert_eq!(1, sim.generation());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut grid: Grid = "ab#\n#ef".parse().unwrap();
+
+        assert_eq!(
+            vec![&['a', 'b', '#'][..], &['#', 'e', 'f'][..]],
+            grid.rows().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["a#", "be", "#f"],
+            grid.columns()
+                .map(|c| c.collect::<String>())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(((2, 1), &'f')), grid.iter().last());
+        assert_eq!(
+            vec![(2, 0), (0, 1)],
+            grid.positions('#').collect::<Vec<_>>()
+        );
+        assert_eq!(Some((2, 0)), grid.find('#'));
+        assert_eq!(None, grid.find('z'));
+
+        let swapped = grid.map(|_, c| if c == '#' { '.' } else { '#' });
+        assert_eq!("##.\n.##\n", swapped.to_string());
+
+        for ((x, _), c) in grid.iter_mut() {
+            if x == 1 {
+                *c = '|';
+            }
+        }
+        assert_eq!("a|#\n#|f\n", grid.to_string());
+
+        let empty = Grid::new(0, 2, '.');
+        assert_eq!(2, empty.rows().filter(|row| row.is_empty()).count());
+        assert_eq!(0, empty.columns().count());
+    }
+
+    #[test]
+    #[should_panic(expected = "column 3 out of bounds, width 3")]
+    fn test_column_out_of_bounds() {
+        let grid: Grid = "ab#\n#ef".parse().unwrap();
+        grid.column(3).count();
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid: Grid = "abc\ndef".parse().unwrap();
+
+        assert_eq!("ad\nbe\ncf\n", grid.transpose().to_string());
+        assert_eq!("cba\nfed\n", grid.flip_horizontal().to_string());
+        assert_eq!("def\nabc\n", grid.flip_vertical().to_string());
+        assert_eq!("da\neb\nfc\n", grid.rotate(1).to_string());
+        assert_eq!("fed\ncba\n", grid.rotate(2).to_string());
+        assert_eq!("cf\nbe\nad\n", grid.rotate(3).to_string());
+        assert_eq!(grid, grid.rotate(4));
+        assert_eq!(grid.rotate(1), grid.transpose().flip_horizontal());
+        assert_eq!(
+            [
+                vec!['a', 'b', 'c'],
+                vec!['c', 'f'],
+                vec!['d', 'e', 'f'],
+                vec!['a', 'd']
+            ],
+            grid.borders()
+        );
+    }
+
+    #[test]
+    fn test_pattern_search() {
+        let monster: Grid = "                  # \n\
+                             #    ##    ##    ###\n \
+                             #  #  #  #  #  #   "
+            .parse()
+            .unwrap();
+        let mut sea = Grid::new(24, 8, '.');
+        for &(x, y) in [(0, 0), (3, 4)].iter() {
+            sea.mark_pattern(
+                &monster,
+                &PatternMatch {
+                    orientation: Orientation::all()[0],
+                    position: (x, y),
+                },
+                ' ',
+                '#',
+            );
+        }
+        let sea = sea.rotate(1).flip_vertical();
+
+        let found = sea.find_pattern_oriented(&monster, ' ');
+        assert_eq!(2, found.len());
+        assert!(found.iter().all(|m| m.orientation == found[0].orientation));
+        assert_eq!(
+            2,
+            sea.find_pattern(&found[0].orientation.apply(&monster), ' ')
+                .len()
+        );
+        assert!(sea.find_pattern(&monster, ' ').is_empty());
+
+        let mut marked = sea.clone();
+        for m in found.iter() {
+            marked.mark_pattern(&monster, m, ' ', 'O');
+        }
+        assert_eq!(0, marked.count_type('#'));
+        assert_eq!(30, marked.count_type('O'));
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = test_grid();
+        let view = grid.view((1, 1), 4, 3);
+
+        assert_eq!(Some(&'#'), view.get((0, 1)));
+        assert_eq!(None, view.get((4, 0)));
+        assert_eq!("...#\n#...\n.#.#\n", view.to_grid().to_string());
+        assert_eq!(view.to_grid(), grid.crop((1, 1), 4, 3));
+    }
+
     #[test]
     fn test_get_first_in_line() {
         let grid = test_grid();
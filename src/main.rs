@@ -1,16 +1,143 @@
+mod bitgrid;
+mod cli;
+mod export;
+mod expr;
+mod grammar;
 mod grid;
 mod passport;
+mod pathfinding;
 mod solutions;
 mod solver;
-use crate::solutions::solve;
+mod visualize;
+mod vm;
+use crate::cli::{
+    control_flow, debug, explain, grid_transform, homework, life, maze, passport_generate,
+    passport_normalise, passport_report, DebugOptions, GridOptions,
+};
+use crate::solutions::{export, solve, visualize};
 use std::env;
 
+fn parse_day(arg: Option<&String>) -> u32 {
+    arg.map(String::as_str).unwrap_or("19").parse().unwrap()
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let found = args.iter().any(|a| a == flag);
+    args.retain(|a| a != flag);
+    found
+}
+
+fn take_option(args: &mut Vec<String>, option: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == option)?;
+    args.remove(i);
+    if i < args.len() {
+        Some(args.remove(i))
+    } else {
+        None
+    }
+}
+
 fn main() {
-    let day: u32 = env::args()
-        .nth(1)
-        .unwrap_or(String::from("19"))
-        .parse()
-        .unwrap();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let visualize_flag = take_flag(&mut args, "--visualize");
+    let fps = take_option(&mut args, "--fps").map_or(10, |fps| fps.parse().unwrap());
 
-    solve(day);
+    match args.first().map(String::as_str) {
+        Some("export") => export(
+            parse_day(args.get(1)),
+            args.get(2).map_or("export.gif", String::as_str),
+        ),
+        Some("passport-report") => {
+            let verbose = take_flag(&mut args, "--verbose");
+            let rules = take_option(&mut args, "--rules");
+            passport_report(args.get(1).map(String::as_str), rules.as_deref(), verbose)
+        }
+        Some("passport-generate") => {
+            let seed = take_option(&mut args, "--seed").map_or(0, |s| s.parse().unwrap());
+            let invalid = take_option(&mut args, "--invalid").map_or(0.5, |s| s.parse().unwrap());
+            let count = args.get(1).map_or(100, |s| s.parse().unwrap());
+            passport_generate(count, seed, invalid)
+        }
+        Some("passport-normalise") => {
+            let json = take_flag(&mut args, "--json");
+            let units = take_option(&mut args, "--units");
+            passport_normalise(args.get(1).map(String::as_str), json, units.as_deref())
+        }
+        Some("debug") => {
+            let mut breakpoints = Vec::new();
+            while let Some(pc) = take_option(&mut args, "--break") {
+                breakpoints.push(pc.parse().unwrap());
+            }
+            let options = DebugOptions {
+                breakpoints,
+                watch: take_flag(&mut args, "--watch"),
+                step_limit: take_option(&mut args, "--max-steps").map(|n| n.parse().unwrap()),
+                detect_loops: !take_flag(&mut args, "--no-loop-detection"),
+            };
+            debug(
+                args.get(1).map_or("inputs/day8.txt", String::as_str),
+                options,
+            )
+        }
+        Some("cfg") => control_flow(
+            args.get(1).map_or("inputs/day8.txt", String::as_str),
+            args.get(2).map(String::as_str),
+        ),
+        Some("homework") => {
+            let mode = take_option(&mut args, "--mode");
+            homework(
+                args.get(1).map_or("inputs/day18.txt", String::as_str),
+                mode.as_deref().unwrap_or("addition"),
+            )
+        }
+        Some("explain") => {
+            let mode = take_option(&mut args, "--mode");
+            match args.get(1) {
+                Some(expression) => explain(
+                    expression,
+                    args.get(2).map_or("inputs/day18.txt", String::as_str),
+                    mode.as_deref(),
+                ),
+                None => eprintln!("explain needs an expression or a line number"),
+            }
+        }
+        Some("grid") => {
+            let options = GridOptions {
+                borders: take_flag(&mut args, "--borders"),
+                count: take_option(&mut args, "--count").map(|c| c.parse().unwrap()),
+                pattern: take_option(&mut args, "--find"),
+                wildcard: take_option(&mut args, "--wildcard").map_or(' ', |c| c.parse().unwrap()),
+                mark: take_option(&mut args, "--mark").map(|c| c.parse().unwrap()),
+            };
+            match args.get(1) {
+                Some(path) => grid_transform(path, &args[2..], options),
+                None => eprintln!("grid needs a grid file"),
+            }
+        }
+        Some("maze") => {
+            let search = take_option(&mut args, "--search");
+            let diagonal = take_flag(&mut args, "--diagonal");
+            let regions = take_flag(&mut args, "--regions");
+            match args.get(1) {
+                Some(path) => maze(
+                    path,
+                    search.as_deref().unwrap_or("a-star"),
+                    diagonal,
+                    regions,
+                ),
+                None => eprintln!("maze needs a grid file"),
+            }
+        }
+        Some("life") => {
+            let generations =
+                take_option(&mut args, "--generations").map_or(1000, |n| n.parse().unwrap());
+            let bits = take_flag(&mut args, "--bits");
+            match args.get(1) {
+                Some(path) => life(path, generations, bits),
+                None => eprintln!("life needs a grid file"),
+            }
+        }
+        _ if visualize_flag => visualize(parse_day(args.first()), fps),
+        _ => solve(parse_day(args.first())),
+    }
 }
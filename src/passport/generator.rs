@@ -0,0 +1,290 @@
+use super::{EyeColour, Field, HairColour, Height, Passport, Reason, Record};
+use std::fmt::{self, Display, Formatter};
+
+// xorshift64*, good enough for test data and stable across platforms.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift.
+        Rng {
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub fn below(&mut self, n: u32) -> u32 {
+        (self.next_u64() % n as u64) as u32
+    }
+
+    // Inclusive on both ends.
+    pub fn range(&mut self, lo: u32, hi: u32) -> u32 {
+        lo + self.below(hi - lo + 1)
+    }
+
+    pub fn chance(&mut self, ratio: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < ratio
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u32) as usize]
+    }
+}
+
+// The single field an invalid passport was generated to fail on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Defect {
+    pub field: Field,
+    pub reason: Reason,
+}
+
+impl Display for Defect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.reason)
+    }
+}
+
+// Every defect the puzzle rules can produce.
+const DEFECTS: [(Field, Reason); 19] = [
+    (Field::Byr, Reason::Missing),
+    (Field::Byr, Reason::OutOfRange),
+    (Field::Byr, Reason::WrongFormat),
+    (Field::Iyr, Reason::Missing),
+    (Field::Iyr, Reason::OutOfRange),
+    (Field::Iyr, Reason::WrongFormat),
+    (Field::Eyr, Reason::Missing),
+    (Field::Eyr, Reason::OutOfRange),
+    (Field::Eyr, Reason::WrongFormat),
+    (Field::Hgt, Reason::Missing),
+    (Field::Hgt, Reason::OutOfRange),
+    (Field::Hgt, Reason::BadUnit),
+    (Field::Hgt, Reason::WrongFormat),
+    (Field::Hcl, Reason::Missing),
+    (Field::Hcl, Reason::WrongFormat),
+    (Field::Ecl, Reason::Missing),
+    (Field::Ecl, Reason::NotAllowed),
+    (Field::Pid, Reason::Missing),
+    (Field::Pid, Reason::WrongFormat),
+];
+
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub record: Record,
+    pub defect: Option<Defect>,
+}
+
+impl Display for Sample {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.record)
+    }
+}
+
+// Passports for the default (puzzle) rules, the same seed always gives the
+// same batch.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    rng: Rng,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn valid(&mut self) -> Passport {
+        let rng = &mut self.rng;
+        Passport {
+            byr: rng.range(1920, 2002),
+            iyr: rng.range(2010, 2020),
+            eyr: rng.range(2020, 2030),
+            hgt: if rng.chance(0.5) {
+                Height::Centimeters(rng.range(150, 193))
+            } else {
+                Height::Inches(rng.range(59, 76))
+            },
+            hcl: HairColour {
+                r: rng.below(256) as u8,
+                g: rng.below(256) as u8,
+                b: rng.below(256) as u8,
+            },
+            ecl: rng.pick(&EyeColour::ALL).clone(),
+            pid: format!("{:09}", rng.below(1_000_000_000)).parse().unwrap(),
+            cid: if rng.chance(0.5) {
+                Some(rng.range(1, 999).to_string())
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn invalid(&mut self) -> Sample {
+        let mut record: Record = self.valid().to_string().parse().unwrap();
+        let &(field, reason) = self.rng.pick(&DEFECTS);
+        match self.broken_value(field, reason) {
+            Some(value) => record.insert(field, value),
+            None => record.remove(field),
+        }
+        Sample {
+            record,
+            defect: Some(Defect { field, reason }),
+        }
+    }
+
+    pub fn sample(&mut self, invalid_ratio: f64) -> Sample {
+        if self.rng.chance(invalid_ratio) {
+            self.invalid()
+        } else {
+            Sample {
+                record: self.valid().to_string().parse().unwrap(),
+                defect: None,
+            }
+        }
+    }
+
+    pub fn batch(&mut self, n: usize, invalid_ratio: f64) -> Vec<Sample> {
+        (0..n).map(|_| self.sample(invalid_ratio)).collect()
+    }
+
+    // A value that fails `field` for exactly `reason`, `None` to leave it out.
+    fn broken_value(&mut self, field: Field, reason: Reason) -> Option<String> {
+        let rng = &mut self.rng;
+        let value = match (field, reason) {
+            (_, Reason::Missing) => return None,
+            (Field::Byr, Reason::OutOfRange) => out_of(rng, 1920, 2002).to_string(),
+            (Field::Iyr, Reason::OutOfRange) => out_of(rng, 2010, 2020).to_string(),
+            (Field::Eyr, Reason::OutOfRange) => out_of(rng, 2020, 2030).to_string(),
+            (Field::Byr, _) | (Field::Iyr, _) | (Field::Eyr, _) => {
+                rng.pick(&["19x0", "twenty", "2o10", "-"]).to_string()
+            }
+            (Field::Hgt, Reason::OutOfRange) => {
+                if rng.chance(0.5) {
+                    Height::Centimeters(out_of(rng, 150, 193)).to_string()
+                } else {
+                    Height::Inches(out_of(rng, 59, 76)).to_string()
+                }
+            }
+            (Field::Hgt, Reason::BadUnit) => {
+                format!(
+                    "{}{}",
+                    rng.range(1, 300),
+                    rng.pick(&["mm", "m", "ft", "px"])
+                )
+            }
+            (Field::Hgt, _) => rng.pick(&["170", "cm", "tall", "1.8m"]).to_string(),
+            (Field::Hcl, _) => match rng.below(3) {
+                0 => format!("{:06x}", rng.below(1 << 24)),
+                1 => format!("#{:05x}", rng.below(1 << 20)),
+                _ => format!("#{:05x}z", rng.below(1 << 20)),
+            },
+            (Field::Ecl, _) => rng.pick(&["red", "wat", "xry", "zzz", "blue"]).to_string(),
+            (Field::Pid, _) => match rng.below(3) {
+                0 => format!("{:08}", rng.below(100_000_000)),
+                1 => format!("{:010}", rng.next_u64() % 10_000_000_000),
+                _ => format!("{:08}x", rng.below(100_000_000)),
+            },
+            (Field::Cid, _) => unreachable!("cid accepts anything"),
+        };
+        Some(value)
+    }
+}
+
+// A number just outside `lo..=hi`, on either side.
+fn out_of(rng: &mut Rng, lo: u32, hi: u32) -> u32 {
+    if rng.chance(0.5) {
+        lo - rng.range(1, 50).min(lo)
+    } else {
+        hi + rng.range(1, 50)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passport::{split_batch, write_batch, BatchReport, DEFAULT_RULES};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_seeded() {
+        let a = Generator::new(42).batch(20, 0.5);
+        let b = Generator::new(42).batch(20, 0.5);
+        let c = Generator::new(43).batch(20, 0.5);
+
+        let texts =
+            |samples: &[Sample]| -> Vec<String> { samples.iter().map(|s| s.to_string()).collect() };
+        assert_eq!(texts(&a), texts(&b));
+        assert_ne!(texts(&a), texts(&c));
+    }
+
+    #[test]
+    fn test_verdict_matches_tag() {
+        for seed in 0..50 {
+            for sample in Generator::new(seed).batch(40, 0.7) {
+                let text = sample.to_string();
+                let errors = sample.record.report(&DEFAULT_RULES);
+                match sample.defect {
+                    None => {
+                        assert!(errors.is_empty(), "{}: {:?}", text, errors);
+                        assert!(text.parse::<Passport>().is_ok(), "{}", text);
+                    }
+                    Some(defect) => {
+                        assert_eq!(
+                            vec![Some((defect.field, defect.reason))],
+                            errors.iter().map(|e| e.reason()).collect::<Vec<_>>(),
+                            "{}",
+                            text
+                        );
+                        assert_eq!(
+                            errors.into_iter().next(),
+                            text.parse::<Passport>().err(),
+                            "{}",
+                            text
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_report_matches_tags() {
+        let samples = Generator::new(7).batch(200, 0.4);
+        let mut out = Vec::new();
+        write_batch(&mut out, &samples).unwrap();
+        let batch = String::from_utf8(out).unwrap();
+        assert_eq!(200, split_batch(&batch).count());
+
+        let report = BatchReport::new(&batch, &DEFAULT_RULES);
+        let invalid = samples.iter().filter(|s| s.defect.is_some()).count();
+        assert_eq!(invalid, report.invalid());
+        assert_eq!(0, report.unreadable);
+        let mut by_reason = BTreeMap::new();
+        for defect in samples.iter().filter_map(|s| s.defect) {
+            *by_reason.entry(defect.reason).or_insert(0) += 1;
+        }
+        assert_eq!(by_reason, report.by_reason);
+        for (field, reason) in DEFECTS.iter() {
+            let tagged = samples
+                .iter()
+                .filter(|s| {
+                    s.defect
+                        == Some(Defect {
+                            field: *field,
+                            reason: *reason,
+                        })
+                })
+                .count();
+            assert!(tagged > 0, "no {} {} sample", field, reason);
+        }
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+mod generator;
+mod report;
+mod rules;
+mod types;
+mod writer;
+pub use generator::Generator;
+pub use report::BatchReport;
+pub use rules::{Reason, RuleSet, DEFAULT_RULES};
+pub use types::{EyeColour, HairColour, Height, PassportId};
+pub use writer::{write_batch, write_json};
+
+lazy_static! {
+    pub static ref HGT_RE: Regex = Regex::new(r"^(\d+)(\D+)$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Field {
+    Byr,
+    Iyr,
+    Eyr,
+    Hgt,
+    Hcl,
+    Ecl,
+    Pid,
+    Cid,
+}
+
+impl Field {
+    pub const ALL: [Field; 8] = [
+        Field::Byr,
+        Field::Iyr,
+        Field::Eyr,
+        Field::Hgt,
+        Field::Hcl,
+        Field::Ecl,
+        Field::Pid,
+        Field::Cid,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Byr => "byr",
+            Field::Iyr => "iyr",
+            Field::Eyr => "eyr",
+            Field::Hgt => "hgt",
+            Field::Hcl => "hcl",
+            Field::Ecl => "ecl",
+            Field::Pid => "pid",
+            Field::Cid => "cid",
+        }
+    }
+
+    pub fn is_required(self) -> bool {
+        self != Field::Cid
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Field {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Field::ALL
+            .iter()
+            .find(|field| field.name() == s)
+            .copied()
+            .ok_or_else(|| ParseError::UnknownField(s.to_owned()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passport {
+    pub byr: u32,
+    pub iyr: u32,
+    pub eyr: u32,
+    pub hgt: Height,
+    pub hcl: HairColour,
+    pub ecl: EyeColour,
+    pub pid: PassportId,
+    pub cid: Option<String>,
+}
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    MalformedToken(String),
+    UnknownField(String),
+    DuplicateField(Field),
+    MissingField(Field),
+    InvalidField {
+        field: Field,
+        value: String,
+        reason: Reason,
+    },
+}
+
+impl ParseError {
+    // Only problems with a single field have a reason, unreadable records don't.
+    pub fn reason(&self) -> Option<(Field, Reason)> {
+        match self {
+            ParseError::MissingField(field) => Some((*field, Reason::Missing)),
+            ParseError::InvalidField { field, reason, .. } => Some((*field, *reason)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MalformedToken(token) => write!(f, "malformed token {:?}", token),
+            ParseError::UnknownField(name) => write!(f, "unknown field {}", name),
+            ParseError::DuplicateField(field) => write!(f, "duplicate field {}", field),
+            ParseError::MissingField(field) => write!(f, "missing field {}", field),
+            ParseError::InvalidField {
+                field,
+                value,
+                reason,
+            } => write!(f, "invalid value {:?} for {}: {}", value, field, reason),
+        }
+    }
+}
+
+impl Passport {
+    // Values in the canonical field order, `cid` only when present.
+    fn values(&self) -> Vec<(Field, String)> {
+        let mut values = vec![
+            (Field::Byr, self.byr.to_string()),
+            (Field::Iyr, self.iyr.to_string()),
+            (Field::Eyr, self.eyr.to_string()),
+            (Field::Hgt, self.hgt.to_string()),
+            (Field::Hcl, self.hcl.to_string()),
+            (Field::Ecl, self.ecl.to_string()),
+            (Field::Pid, self.pid.to_string()),
+        ];
+        if let Some(cid) = &self.cid {
+            values.push((Field::Cid, cid.clone()));
+        }
+        values
+    }
+
+    // A passport written out by `Display` only reads back under rules that
+    // accept it, which for custom rules aren't the puzzle's.
+    pub fn parse_with(s: &str, rules: &RuleSet) -> Result<Passport, ParseError> {
+        s.parse::<Record>()?.validate_with(rules)
+    }
+
+    pub fn to_json(&self) -> String {
+        let members: Vec<String> = self
+            .values()
+            .into_iter()
+            .map(|(field, value)| match field {
+                Field::Byr | Field::Iyr | Field::Eyr => format!("\"{}\":{}", field, value),
+                _ => format!("\"{}\":{}", field, writer::json_string(&value)),
+            })
+            .collect();
+        format!("{{{}}}", members.join(","))
+    }
+}
+
+// A single line of `key:value` pairs, which parses back into the same passport
+// under the rules it was validated with.
+impl Display for Passport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .values()
+            .iter()
+            .map(|(field, value)| format!("{}:{}", field, value))
+            .collect();
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+// The raw `key:value` pairs of one passport, before any value is checked.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Record {
+    fields: BTreeMap<Field, String>,
+}
+
+impl Record {
+    pub fn get(&self, field: Field) -> Option<&str> {
+        self.fields.get(&field).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, field: Field, value: String) {
+        self.fields.insert(field, value);
+    }
+
+    pub fn remove(&mut self, field: Field) {
+        self.fields.remove(&field);
+    }
+
+    pub fn missing_fields(&self) -> Vec<Field> {
+        Field::ALL
+            .iter()
+            .copied()
+            .filter(|f| f.is_required() && !self.fields.contains_key(f))
+            .collect()
+    }
+
+    pub fn has_required_fields(&self) -> bool {
+        self.missing_fields().is_empty()
+    }
+
+    // The raw value of a required field, once it passed its rule.
+    fn checked(&self, field: Field, rules: &RuleSet) -> Result<&str, ParseError> {
+        let value = self.get(field).ok_or(ParseError::MissingField(field))?;
+        rules
+            .check(field, value)
+            .map_err(|reason| invalid(field, value, reason))?;
+        Ok(value)
+    }
+
+    pub fn check_field(&self, field: Field, rules: &RuleSet) -> Result<(), ParseError> {
+        if self.get(field).is_none() && !field.is_required() {
+            return Ok(());
+        }
+        let value = self.checked(field, rules)?;
+        match field {
+            Field::Byr | Field::Iyr | Field::Eyr => extract_number(value, field).map(|_| ()),
+            Field::Hgt => typed::<Height>(field, value).map(|_| ()),
+            Field::Hcl => typed::<HairColour>(field, value).map(|_| ()),
+            Field::Ecl => typed::<EyeColour>(field, value).map(|_| ()),
+            Field::Pid => typed::<PassportId>(field, value).map(|_| ()),
+            Field::Cid => Ok(()),
+        }
+    }
+
+    // Every failing field instead of only the first one `validate` stops at.
+    pub fn report(&self, rules: &RuleSet) -> Vec<ParseError> {
+        Field::ALL
+            .iter()
+            .filter_map(|&field| self.check_field(field, rules).err())
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<Passport, ParseError> {
+        self.validate_with(&DEFAULT_RULES)
+    }
+
+    pub fn validate_with(&self, rules: &RuleSet) -> Result<Passport, ParseError> {
+        Ok(Passport {
+            byr: extract_number(self.checked(Field::Byr, rules)?, Field::Byr)?,
+            iyr: extract_number(self.checked(Field::Iyr, rules)?, Field::Iyr)?,
+            eyr: extract_number(self.checked(Field::Eyr, rules)?, Field::Eyr)?,
+            hgt: typed(Field::Hgt, self.checked(Field::Hgt, rules)?)?,
+            hcl: typed(Field::Hcl, self.checked(Field::Hcl, rules)?)?,
+            ecl: typed(Field::Ecl, self.checked(Field::Ecl, rules)?)?,
+            pid: typed(Field::Pid, self.checked(Field::Pid, rules)?)?,
+            cid: match self.get(Field::Cid) {
+                Some(_) => Some(self.checked(Field::Cid, rules)?.to_owned()),
+                None => None,
+            },
+        })
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(field, value)| format!("{}:{}", field, value))
+            .collect();
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+impl FromStr for Record {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut record = Record::default();
+        for token in s.split_whitespace() {
+            let mut parts = token.splitn(2, ':');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => (key, value),
+                _ => return Err(ParseError::MalformedToken(token.to_owned())),
+            };
+            let field: Field = key.parse()?;
+            if record.fields.insert(field, value.to_owned()).is_some() {
+                return Err(ParseError::DuplicateField(field));
+            }
+        }
+        Ok(record)
+    }
+}
+
+fn invalid(field: Field, value: &str, reason: Reason) -> ParseError {
+    ParseError::InvalidField {
+        field,
+        value: value.to_owned(),
+        reason,
+    }
+}
+
+fn extract_number(value: &str, field: Field) -> Result<u32, ParseError> {
+    value
+        .parse()
+        .map_err(|_| invalid(field, value, Reason::WrongFormat))
+}
+
+// Rules decide which values are acceptable, the types only need to understand them.
+fn typed<T: FromStr<Err = Reason>>(field: Field, value: &str) -> Result<T, ParseError> {
+    value
+        .parse()
+        .map_err(|reason| invalid(field, value, reason))
+}
+
+// Passports in a batch file are separated by blank lines.
+pub fn split_batch(s: &str) -> impl Iterator<Item = &str> {
+    s.split("\n\n").filter(|p| !p.trim().is_empty())
+}
+
+impl FromStr for Passport {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Passport::parse_with(s, &DEFAULT_RULES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rules::Constraint;
+    use super::*;
+
+    #[test]
+    fn test_passport_has_values() {
+        let s1 = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+        hcl:#623a2f";
+        let s2 = "eyr:2029 ecl:blu cid:129 byr:1989
+        iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm";
+        let s3 = "hcl:#888785
+        hgt:164cm byr:2001 iyr:2015 cid:88
+        pid:545766238 ecl:hzl
+        eyr:2022";
+        let s4 = "iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+        let s5 = "eyr:1972 cid:100
+        hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926";
+        let s6 = "iyr:2019
+        hcl:#602927 eyr:1967 hgt:170cm
+        ecl:grn pid:012533040 byr:1946";
+        let s7 = "hcl:dab227 iyr:2012
+        ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277";
+        let s8 = "hgt:59cm ecl:zzz
+        eyr:2038 hcl:74454a iyr:2023
+        pid:3556412378 byr:2007";
+
+        assert!(s1.parse::<Passport>().is_ok());
+        assert!(s2.parse::<Passport>().is_ok());
+        assert!(s3.parse::<Passport>().is_ok());
+        assert!(s4.parse::<Passport>().is_ok());
+        assert!(s5.parse::<Passport>().is_err());
+        assert!(s6.parse::<Passport>().is_err());
+        assert!(s7.parse::<Passport>().is_err());
+        assert!(s8.parse::<Passport>().is_err());
+    }
+
+    #[test]
+    fn test_record_errors() {
+        let record: Record = "hcl:#cfa07d eyr:2025 pid:166559648
+        iyr:2011 ecl:brn hgt:59in"
+            .parse()
+            .unwrap();
+        assert_eq!(Some("59in"), record.get(Field::Hgt));
+        assert_eq!(None, record.get(Field::Cid));
+        assert_eq!(vec![Field::Byr], record.missing_fields());
+        assert_eq!(
+            Err(ParseError::MissingField(Field::Byr)),
+            record.validate().map(|_| ())
+        );
+
+        assert_eq!(
+            Err(ParseError::UnknownField(String::from("xyz"))),
+            "byr:1990 xyz:1".parse::<Record>()
+        );
+        assert_eq!(
+            Err(ParseError::DuplicateField(Field::Byr)),
+            "byr:1990 byr:1991".parse::<Record>()
+        );
+        assert_eq!(
+            Err(ParseError::MalformedToken(String::from("byr1990"))),
+            "byr1990".parse::<Record>()
+        );
+        assert_eq!(
+            Err(invalid(Field::Byr, "1900", Reason::OutOfRange)),
+            "byr:1900 iyr:2012".parse::<Passport>().map(|_| ())
+        );
+
+        let passport: Passport =
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 cid:147 hgt:183cm"
+                .parse()
+                .unwrap();
+        assert_eq!(Some(String::from("147")), passport.cid);
+    }
+
+    #[test]
+    fn test_report() {
+        let record: Record = "ecl:gry pid:86003332 hcl:fffffd byr:1937 iyr:2037 hgt:183"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                invalid(Field::Iyr, "2037", Reason::OutOfRange),
+                ParseError::MissingField(Field::Eyr),
+                invalid(Field::Hgt, "183", Reason::WrongFormat),
+                invalid(Field::Hcl, "fffffd", Reason::WrongFormat),
+                invalid(Field::Pid, "86003332", Reason::WrongFormat),
+            ],
+            record.report(&DEFAULT_RULES)
+        );
+        assert_eq!(
+            Some((Field::Eyr, Reason::Missing)),
+            record.report(&DEFAULT_RULES)[1].reason()
+        );
+        assert_eq!(
+            Err(record.report(&DEFAULT_RULES).remove(0)),
+            record.validate().map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let passport: Passport = "hcl:#888785
+        hgt:164cm byr:2001 iyr:2015 cid:88
+        pid:045766238 ecl:hzl
+        eyr:2022"
+            .parse()
+            .unwrap();
+        let s = passport.to_string();
+
+        assert_eq!(
+            "byr:2001 iyr:2015 eyr:2022 hgt:164cm hcl:#888785 ecl:hzl pid:045766238 cid:88",
+            s
+        );
+        assert_eq!(Ok(passport), s.parse());
+
+        let record: Record = "pid:1 hgt:59 byr:x".parse().unwrap();
+        assert_eq!("byr:x hgt:59 pid:1", record.to_string());
+        assert_eq!(Ok(record.clone()), record.to_string().parse());
+    }
+
+    #[test]
+    fn test_custom_rules_end_to_end() {
+        // Whatever a loaded rule set accepts has to make it into a passport.
+        let rules: RuleSet = "byr: range 1800-1900\nhgt: units 100-300cm\npid: regex ^\\d{9}$"
+            .parse()
+            .unwrap();
+        let record: Record = "byr:1850 iyr:1 eyr:2 hgt:250cm hcl:#000000 ecl:blu pid:000000042"
+            .parse()
+            .unwrap();
+        let passport = record.validate_with(&rules).unwrap();
+        assert_eq!(1850, passport.byr);
+        assert_eq!(Height::Centimeters(250), passport.hgt);
+        assert!(record.report(&rules).is_empty());
+
+        let mut short = record.clone();
+        short.insert(Field::Hgt, String::from("60in"));
+        assert_eq!(
+            Err(invalid(Field::Hgt, "60in", Reason::BadUnit)),
+            short.validate_with(&rules).map(|_| ())
+        );
+        assert!(record.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_with_rules() {
+        let record: Record =
+            "ecl:red pid:1 eyr:2099 hcl:#ABCDEF byr:1800 iyr:2017 hgt:183in cid:abc"
+                .parse()
+                .unwrap();
+        assert!(record.validate().is_err());
+
+        let rules: RuleSet = "ecl: one-of red\nhgt: units 100-200in\ncid: regex ^\\d+$"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Err(invalid(Field::Cid, "abc", Reason::WrongFormat)),
+            record.validate_with(&rules).map(|_| ())
+        );
+
+        let mut rules = rules;
+        rules.set(Field::Cid, Constraint::Any);
+        let passport = record.validate_with(&rules).unwrap();
+        assert_eq!(1800, passport.byr);
+        assert_eq!("red", passport.ecl.code());
+        assert_eq!(Height::Inches(183), passport.hgt);
+        assert_eq!("#abcdef", passport.hcl.to_string());
+        assert_eq!("1", passport.pid.as_str());
+        assert!(record.report(&rules).is_empty());
+
+        // The puzzle codes still map onto their own variants.
+        let mut other = record.clone();
+        other.insert(Field::Ecl, String::from("oth"));
+        rules.set(Field::Ecl, Constraint::Any);
+        assert_eq!(EyeColour::Other, other.validate_with(&rules).unwrap().ecl);
+    }
+}
@@ -0,0 +1,112 @@
+use super::{split_batch, Field, ParseError, Reason, Record, RuleSet};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+// Outcome of validating every passport of a batch file.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub total: usize,
+    pub valid: usize,
+    // Passports that could not even be split into known `key:value` fields.
+    pub unreadable: usize,
+    pub by_reason: BTreeMap<Reason, usize>,
+    pub by_field: BTreeMap<Field, usize>,
+    // Position in the batch (starting at 1) and every error of each bad passport.
+    pub failures: Vec<(usize, Vec<ParseError>)>,
+}
+
+impl BatchReport {
+    pub fn new(batch: &str, rules: &RuleSet) -> Self {
+        let mut report = BatchReport::default();
+
+        for (i, passport) in split_batch(batch).enumerate() {
+            report.total += 1;
+            let errors = match passport.parse::<Record>() {
+                Ok(record) => record.report(rules),
+                Err(e) => {
+                    report.unreadable += 1;
+                    vec![e]
+                }
+            };
+            if errors.is_empty() {
+                report.valid += 1;
+                continue;
+            }
+            for (field, reason) in errors.iter().filter_map(ParseError::reason) {
+                *report.by_reason.entry(reason).or_insert(0) += 1;
+                *report.by_field.entry(field).or_insert(0) += 1;
+            }
+            report.failures.push((i + 1, errors));
+        }
+
+        report
+    }
+
+    pub fn invalid(&self) -> usize {
+        self.total - self.valid
+    }
+}
+
+// The summary only, `failures` is left for callers that want the details.
+impl Display for BatchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "passports: {}", self.total)?;
+        writeln!(f, "valid: {}", self.valid)?;
+        writeln!(f, "invalid: {}", self.invalid())?;
+        if self.unreadable > 0 {
+            writeln!(f, "unreadable: {}", self.unreadable)?;
+        }
+        writeln!(f, "failures by reason:")?;
+        for (reason, count) in self.by_reason.iter() {
+            writeln!(f, "  {}: {}", reason, count)?;
+        }
+        writeln!(f, "failures by field:")?;
+        for (field, count) in self.by_field.iter() {
+            writeln!(f, "  {}: {}", field, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passport::DEFAULT_RULES;
+
+    #[test]
+    fn test_batch_report() {
+        let batch = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179in
+
+foo:bar byr:1931
+";
+        let report = BatchReport::new(batch, &DEFAULT_RULES);
+
+        assert_eq!(4, report.total);
+        assert_eq!(1, report.valid);
+        assert_eq!(3, report.invalid());
+        assert_eq!(1, report.unreadable);
+        assert_eq!(Some(&1), report.by_reason.get(&Reason::Missing));
+        assert_eq!(Some(&1), report.by_reason.get(&Reason::OutOfRange));
+        assert_eq!(Some(&2), report.by_field.get(&Field::Hgt));
+        assert_eq!(
+            vec![2, 3, 4],
+            report.failures.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![ParseError::UnknownField(String::from("foo"))],
+            report.failures[2].1
+        );
+        assert!(report.to_string().contains("  missing: 1\n"));
+    }
+}
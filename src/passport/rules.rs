@@ -0,0 +1,299 @@
+use super::Field;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    ops::RangeInclusive,
+    path::Path,
+    str::FromStr,
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref RANGE_RE: Regex = Regex::new(r"^(\d+)-(\d+)$").unwrap();
+    static ref UNIT_RANGE_RE: Regex = Regex::new(r"^(\d+)-(\d+)(\D+)$").unwrap();
+    static ref MEASURE_RE: Regex = Regex::new(r"^(\d+)(\D+)$").unwrap();
+    pub static ref DEFAULT_RULES: RuleSet = DEFAULT_CONFIG.parse().unwrap();
+}
+
+// The units `Height` understands.
+const UNITS: [&str; 2] = ["cm", "in"];
+
+// The rules from the day 4 puzzle, in the same format `RuleSet::load` reads.
+pub const DEFAULT_CONFIG: &str = r"# field: kind arguments...
+byr: range 1920-2002
+iyr: range 2010-2020
+eyr: range 2020-2030
+hgt: units 150-193cm 59-76in
+hcl: regex ^#[0-9a-f]{6}$
+ecl: one-of amb blu brn gry grn hzl oth
+pid: regex ^\d{9}$
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Reason {
+    Missing,
+    OutOfRange,
+    BadUnit,
+    WrongFormat,
+    NotAllowed,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Reason::Missing => "missing",
+            Reason::OutOfRange => "out of range",
+            Reason::BadUnit => "bad unit",
+            Reason::WrongFormat => "wrong format",
+            Reason::NotAllowed => "not an allowed value",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Constraint {
+    Any,
+    Range(RangeInclusive<u32>),
+    // A number directly followed by its unit, each unit with its own range.
+    Units(Vec<(String, RangeInclusive<u32>)>),
+    Pattern(Regex),
+    OneOf(Vec<String>),
+}
+
+impl Constraint {
+    pub fn check(&self, value: &str) -> Result<(), Reason> {
+        match self {
+            Constraint::Any => Ok(()),
+            Constraint::Range(range) => {
+                let n: u32 = value.parse().map_err(|_| Reason::WrongFormat)?;
+                if range.contains(&n) {
+                    Ok(())
+                } else {
+                    Err(Reason::OutOfRange)
+                }
+            }
+            Constraint::Units(units) => {
+                let captures = MEASURE_RE.captures(value).ok_or(Reason::WrongFormat)?;
+                let n: u32 = captures[1].parse().map_err(|_| Reason::WrongFormat)?;
+                let (_, range) = units
+                    .iter()
+                    .find(|(unit, _)| unit == &captures[2])
+                    .ok_or(Reason::BadUnit)?;
+                if range.contains(&n) {
+                    Ok(())
+                } else {
+                    Err(Reason::OutOfRange)
+                }
+            }
+            Constraint::Pattern(regex) => {
+                if regex.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(Reason::WrongFormat)
+                }
+            }
+            Constraint::OneOf(values) => {
+                if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(Reason::NotAllowed)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RuleError {
+    Io(io::Error),
+    Syntax { line: usize, message: String },
+}
+
+impl Display for RuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Io(e) => write!(f, "{}", e),
+            RuleError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl From<io::Error> for RuleError {
+    fn from(e: io::Error) -> Self {
+        RuleError::Io(e)
+    }
+}
+
+// Constraint per field, fields without one accept any value.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    constraints: BTreeMap<Field, Constraint>,
+}
+
+impl RuleSet {
+    pub fn load(path: &Path) -> Result<Self, RuleError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn set(&mut self, field: Field, constraint: Constraint) {
+        self.constraints.insert(field, constraint);
+    }
+
+    pub fn check(&self, field: Field, value: &str) -> Result<(), Reason> {
+        match self.constraints.get(&field) {
+            Some(constraint) => constraint.check(value),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_range(s: &str) -> Option<RangeInclusive<u32>> {
+    let captures = RANGE_RE.captures(s)?;
+    Some(captures[1].parse().ok()?..=captures[2].parse().ok()?)
+}
+
+fn parse_unit_range(s: &str) -> Result<(String, RangeInclusive<u32>), String> {
+    let syntax = || format!("expected min-maxunit, found {:?}", s);
+    let captures = UNIT_RANGE_RE.captures(s).ok_or_else(syntax)?;
+    let min = captures[1].parse().map_err(|_| syntax())?;
+    let max = captures[2].parse().map_err(|_| syntax())?;
+    // A rule for a unit the height type can't read would reject every value.
+    if !UNITS.contains(&&captures[3]) {
+        return Err(format!(
+            "unknown unit {:?}, expected one of {}",
+            &captures[3],
+            UNITS.join(" ")
+        ));
+    }
+    Ok((captures[3].to_owned(), min..=max))
+}
+
+// `rest` is everything after the kind, a regex takes all of it as the pattern
+// so it may contain spaces.
+fn parse_constraint(kind: &str, rest: &str) -> Result<Constraint, String> {
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    match (kind, args.as_slice()) {
+        ("any", []) => Ok(Constraint::Any),
+        ("range", [range]) => parse_range(range)
+            .map(Constraint::Range)
+            .ok_or_else(|| format!("expected min-max, found {:?}", range)),
+        ("units", units) if !units.is_empty() => units
+            .iter()
+            .map(|u| parse_unit_range(u))
+            .collect::<Result<_, _>>()
+            .map(Constraint::Units),
+        ("regex", [_, ..]) => Regex::new(rest)
+            .map(Constraint::Pattern)
+            .map_err(|e| e.to_string()),
+        ("one-of", values) if !values.is_empty() => Ok(Constraint::OneOf(
+            values.iter().map(|v| String::from(*v)).collect(),
+        )),
+        _ => Err(format!("bad arguments for {:?}: {:?}", kind, args)),
+    }
+}
+
+// One `field: kind arguments...` rule per line, `#` starts a comment line.
+impl FromStr for RuleSet {
+    type Err = RuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = RuleSet::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let syntax = |message: String| RuleError::Syntax {
+                line: i + 1,
+                message,
+            };
+
+            let mut halves = line.splitn(2, ':');
+            let field: Field = halves
+                .next()
+                .unwrap()
+                .trim()
+                .parse()
+                .map_err(|e| syntax(format!("{}", e)))?;
+            let mut constraint = halves
+                .next()
+                .unwrap_or("")
+                .trim()
+                .splitn(2, char::is_whitespace);
+            let kind = constraint.next().unwrap();
+            if kind.is_empty() {
+                return Err(syntax(format!("no constraint for {}", field)));
+            }
+            let rest = constraint.next().unwrap_or("").trim();
+            rules.set(field, parse_constraint(kind, rest).map_err(syntax)?);
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules() {
+        let rules = &*DEFAULT_RULES;
+
+        assert_eq!(Ok(()), rules.check(Field::Byr, "2002"));
+        assert_eq!(Err(Reason::OutOfRange), rules.check(Field::Byr, "2003"));
+        assert_eq!(Err(Reason::WrongFormat), rules.check(Field::Byr, "20o2"));
+        assert_eq!(Ok(()), rules.check(Field::Hgt, "60in"));
+        assert_eq!(Err(Reason::OutOfRange), rules.check(Field::Hgt, "190in"));
+        assert_eq!(Err(Reason::BadUnit), rules.check(Field::Hgt, "190mm"));
+        assert_eq!(Err(Reason::WrongFormat), rules.check(Field::Hgt, "190"));
+        assert_eq!(Ok(()), rules.check(Field::Hcl, "#123abc"));
+        assert_eq!(Err(Reason::WrongFormat), rules.check(Field::Hcl, "#123abz"));
+        assert_eq!(Err(Reason::NotAllowed), rules.check(Field::Ecl, "wat"));
+        assert_eq!(
+            Err(Reason::WrongFormat),
+            rules.check(Field::Pid, "0123456789")
+        );
+        assert_eq!(Ok(()), rules.check(Field::Cid, "anything"));
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let rules: RuleSet = "
+            # taller people, metric only
+            hgt: units 100-300cm
+            ecl: one-of red
+            cid: regex ^\\d+$
+            pid: regex ^a b$"
+            .parse()
+            .unwrap();
+
+        assert_eq!(Ok(()), rules.check(Field::Hgt, "200cm"));
+        assert_eq!(Err(Reason::BadUnit), rules.check(Field::Hgt, "70in"));
+        assert_eq!(Ok(()), rules.check(Field::Ecl, "red"));
+        assert_eq!(Err(Reason::WrongFormat), rules.check(Field::Cid, "x1"));
+        assert_eq!(Ok(()), rules.check(Field::Byr, "1"));
+        assert_eq!(Ok(()), rules.check(Field::Pid, "a b"));
+        assert_eq!(Err(Reason::WrongFormat), rules.check(Field::Pid, "a"));
+
+        match "byr: range 1-\nhgt: any".parse::<RuleSet>() {
+            Err(RuleError::Syntax { line: 1, .. }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        match "hgt: units 150-193cm\nhgt: units 1-3m".parse::<RuleSet>() {
+            Err(RuleError::Syntax { line: 2, message }) => {
+                assert_eq!("unknown unit \"m\", expected one of cm in", message)
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+        match "\nfoo: any".parse::<RuleSet>() {
+            Err(RuleError::Syntax { line: 2, message }) => {
+                assert_eq!("unknown field foo", message)
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use super::{Reason, HGT_RE};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+// Equality is structural, so 50in != 127cm; `cmp_length` compares lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Height {
+    Inches(u32),
+    Centimeters(u32),
+}
+
+impl Height {
+    fn micrometres(self) -> u64 {
+        match self {
+            Height::Inches(n) => n as u64 * 25_400,
+            Height::Centimeters(n) => n as u64 * 10_000,
+        }
+    }
+
+    pub fn cmp_length(self, other: Height) -> Ordering {
+        self.micrometres().cmp(&other.micrometres())
+    }
+
+    #[cfg(test)]
+    pub fn same_length(self, other: Height) -> bool {
+        self.cmp_length(other) == Ordering::Equal
+    }
+
+    // Rounded to the nearest whole unit.
+    pub fn to_centimeters(self) -> Height {
+        Height::Centimeters(((self.micrometres() + 5_000) / 10_000) as u32)
+    }
+
+    pub fn to_inches(self) -> Height {
+        Height::Inches(((self.micrometres() + 12_700) / 25_400) as u32)
+    }
+
+    pub fn value(self) -> u32 {
+        match self {
+            Height::Inches(n) | Height::Centimeters(n) => n,
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            Height::Inches(_) => "in",
+            Height::Centimeters(_) => "cm",
+        }
+    }
+}
+
+impl Display for Height {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value(), self.unit())
+    }
+}
+
+impl FromStr for Height {
+    type Err = Reason;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let captures = HGT_RE.captures(s).ok_or(Reason::WrongFormat)?;
+        let n = captures[1].parse().map_err(|_| Reason::WrongFormat)?;
+        match &captures[2] {
+            "cm" => Ok(Height::Centimeters(n)),
+            "in" => Ok(Height::Inches(n)),
+            _ => Err(Reason::BadUnit),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HairColour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Display for HairColour {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+// `#` and six hex digits, the rules decide whether upper case is acceptable.
+impl FromStr for HairColour {
+    type Err = Reason;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').ok_or(Reason::WrongFormat)?;
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Reason::WrongFormat);
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        Ok(HairColour {
+            r: channel(0),
+            g: channel(2),
+            b: channel(4),
+        })
+    }
+}
+
+// The puzzle's colour codes, anything else a rule set allows is `Unlisted`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EyeColour {
+    Amber,
+    Blue,
+    Brown,
+    Grey,
+    Green,
+    Hazel,
+    Other,
+    Unlisted(String),
+}
+
+impl EyeColour {
+    pub const ALL: [EyeColour; 7] = [
+        EyeColour::Amber,
+        EyeColour::Blue,
+        EyeColour::Brown,
+        EyeColour::Grey,
+        EyeColour::Green,
+        EyeColour::Hazel,
+        EyeColour::Other,
+    ];
+
+    pub fn code(&self) -> &str {
+        match self {
+            EyeColour::Amber => "amb",
+            EyeColour::Blue => "blu",
+            EyeColour::Brown => "brn",
+            EyeColour::Grey => "gry",
+            EyeColour::Green => "grn",
+            EyeColour::Hazel => "hzl",
+            EyeColour::Other => "oth",
+            EyeColour::Unlisted(code) => code,
+        }
+    }
+}
+
+impl Display for EyeColour {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+// Which colours are allowed is up to the rules.
+impl FromStr for EyeColour {
+    type Err = Reason;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(Reason::WrongFormat);
+        }
+        Ok(EyeColour::ALL
+            .iter()
+            .find(|colour| colour.code() == s)
+            .cloned()
+            .unwrap_or_else(|| EyeColour::Unlisted(s.to_owned())))
+    }
+}
+
+// Digits kept as text so leading zeros survive, the rules decide how many.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PassportId(String);
+
+// Only the tests need the digits as anything but text.
+#[cfg(test)]
+impl PassportId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // `None` when there are too many digits for a u64.
+    pub fn number(&self) -> Option<u64> {
+        self.0.parse().ok()
+    }
+}
+
+impl Display for PassportId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for PassportId {
+    type Err = Reason;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+            Ok(PassportId(s.to_owned()))
+        } else {
+            Err(Reason::WrongFormat)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height() {
+        let tall: Height = "76in".parse().unwrap();
+        assert_eq!(Height::Inches(76), tall);
+        assert_eq!("76in", tall.to_string());
+        assert_eq!("193cm", tall.to_centimeters().to_string());
+        assert_eq!("59in", Height::Centimeters(150).to_inches().to_string());
+
+        assert_ne!(Height::Inches(50), Height::Centimeters(127));
+        assert!(Height::Inches(50).same_length(Height::Centimeters(127)));
+        assert_eq!(Ordering::Less, Height::Centimeters(193).cmp_length(tall));
+        assert_eq!(Ordering::Greater, Height::Centimeters(194).cmp_length(tall));
+        assert_eq!(
+            Some(Height::Inches(60)),
+            vec![Height::Centimeters(150), Height::Inches(60)]
+                .into_iter()
+                .max_by(|a, b| a.cmp_length(*b))
+        );
+
+        assert_eq!(Err(Reason::BadUnit), "190mm".parse::<Height>());
+        assert_eq!(Err(Reason::WrongFormat), "190".parse::<Height>());
+    }
+
+    #[test]
+    fn test_hair_colour() {
+        let colour: HairColour = "#1a2B3c".parse().unwrap();
+        assert_eq!(
+            HairColour {
+                r: 0x1a,
+                g: 0x2b,
+                b: 0x3c
+            },
+            colour
+        );
+        assert_eq!("#1a2b3c", colour.to_string());
+        assert_eq!(Err(Reason::WrongFormat), "123abc".parse::<HairColour>());
+        assert_eq!(Err(Reason::WrongFormat), "#123abz".parse::<HairColour>());
+        assert_eq!(Err(Reason::WrongFormat), "#123ab".parse::<HairColour>());
+    }
+
+    #[test]
+    fn test_eye_colour() {
+        for colour in EyeColour::ALL.iter() {
+            assert_eq!(Ok(colour.clone()), colour.to_string().parse());
+        }
+        assert_eq!(Ok(EyeColour::Hazel), "hzl".parse());
+        let wat: EyeColour = "wat".parse().unwrap();
+        assert_eq!(EyeColour::Unlisted(String::from("wat")), wat);
+        assert_eq!("wat", wat.to_string());
+        assert_eq!(Err(Reason::WrongFormat), "".parse::<EyeColour>());
+    }
+
+    #[test]
+    fn test_passport_id() {
+        let pid: PassportId = "000000001".parse().unwrap();
+        assert_eq!("000000001", pid.to_string());
+        assert_eq!(Some(1), pid.number());
+        assert_eq!(
+            "0123456789",
+            "0123456789".parse::<PassportId>().unwrap().as_str()
+        );
+        assert_eq!(None, "1".repeat(30).parse::<PassportId>().unwrap().number());
+        assert_eq!(Err(Reason::WrongFormat), "01234567a".parse::<PassportId>());
+        assert_eq!(Err(Reason::WrongFormat), "".parse::<PassportId>());
+    }
+}
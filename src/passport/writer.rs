@@ -0,0 +1,109 @@
+use super::Passport;
+use std::{
+    fmt::{Display, Write as _},
+    io::{self, Write},
+};
+
+// One passport per line with blank lines in between, the same layout
+// `split_batch` reads.
+pub fn write_batch<W: Write, T: Display>(mut w: W, passports: &[T]) -> io::Result<()> {
+    for (i, passport) in passports.iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        writeln!(w, "{}", passport)?;
+    }
+    Ok(())
+}
+
+// A JSON array with one object per passport, years as numbers and every
+// other field as a string.
+pub fn write_json<W: Write>(mut w: W, passports: &[Passport]) -> io::Result<()> {
+    writeln!(w, "[")?;
+    for (i, passport) in passports.iter().enumerate() {
+        let separator = if i + 1 < passports.len() { "," } else { "" };
+        writeln!(w, "  {}{}", passport.to_json(), separator)?;
+    }
+    writeln!(w, "]")
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => write!(result, "\\u{:04x}", c as u32).unwrap(),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passport::{split_batch, RuleSet};
+
+    #[test]
+    fn test_batch_round_trip() {
+        let batch = "eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719
+";
+        let passports: Vec<Passport> = split_batch(batch).map(|p| p.parse().unwrap()).collect();
+
+        let mut out = Vec::new();
+        write_batch(&mut out, &passports).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert_eq!(3, written.split("\n\n").count());
+
+        let reparsed: Vec<Passport> = split_batch(&written).map(|p| p.parse().unwrap()).collect();
+        assert_eq!(passports, reparsed);
+
+        // Values only custom rules accept need the same rules to read back.
+        let rules: RuleSet = "ecl: one-of red\nhgt: units 100-200in\npid: regex ^\\d+$"
+            .parse()
+            .unwrap();
+        let custom = "ecl:red pid:1 eyr:2020 hcl:#ABCDEF byr:1980 iyr:2017 hgt:183in cid:abc";
+        let passports = vec![Passport::parse_with(custom, &rules).unwrap()];
+        let mut out = Vec::new();
+        write_batch(&mut out, &passports).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.parse::<Passport>().is_err());
+        let reparsed: Vec<Passport> = split_batch(&written)
+            .map(|p| Passport::parse_with(p, &rules).unwrap())
+            .collect();
+        assert_eq!(passports, reparsed);
+    }
+
+    #[test]
+    fn test_json() {
+        let passport: Passport =
+            "iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            r##"{"byr":1944,"iyr":2010,"eyr":2021,"hgt":"158cm","hcl":"#b6652a","ecl":"blu","pid":"093154719"}"##,
+            passport.to_json()
+        );
+
+        let mut out = Vec::new();
+        write_json(&mut out, &[passport.clone(), passport]).unwrap();
+        let json = String::from_utf8(out).unwrap();
+        assert!(json.starts_with("[\n  {\"byr\":1944"));
+        assert!(json.ends_with("},\n  {\"byr\":1944,\"iyr\":2010,\"eyr\":2021,\"hgt\":\"158cm\",\"hcl\":\"#b6652a\",\"ecl\":\"blu\",\"pid\":\"093154719\"}\n]\n"));
+
+        assert_eq!(r#""a\"b\\c\u0001""#, json_string("a\"b\\c\u{1}"));
+    }
+}
@@ -0,0 +1,379 @@
+use crate::grid::{Coord, Grid};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+const ORTHOGONAL: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const DIAGONAL: [(i64, i64); 4] = [(1, -1), (1, 1), (-1, 1), (-1, -1)];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Neighbourhood {
+    VonNeumann,
+    Moore,
+}
+
+impl Neighbourhood {
+    pub fn of(self, grid: &Grid, c: impl Coord) -> Vec<(usize, usize)> {
+        let dirs = match self {
+            Neighbourhood::VonNeumann => ORTHOGONAL.iter().chain([].iter()),
+            Neighbourhood::Moore => ORTHOGONAL.iter().chain(DIAGONAL.iter()),
+        };
+
+        dirs.map(|&(dx, dy)| (c.x() as i64 + dx, c.y() as i64 + dy))
+            .filter(|&(x, y)| x >= 0 && y >= 0 && (x as usize) < grid.w && (y as usize) < grid.h)
+            .map(|(x, y)| (x as usize, y as usize))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub cells: Vec<(usize, usize)>,
+    pub cost: usize,
+}
+
+pub fn manhattan(a: impl Coord, b: impl Coord) -> usize {
+    let (dx, dy) = delta(a, b);
+    dx + dy
+}
+
+pub fn chebyshev(a: impl Coord, b: impl Coord) -> usize {
+    let (dx, dy) = delta(a, b);
+    dx.max(dy)
+}
+
+fn delta(a: impl Coord, b: impl Coord) -> (usize, usize) {
+    let dx = if a.x() > b.x() {
+        a.x() - b.x()
+    } else {
+        b.x() - a.x()
+    };
+    let dy = if a.y() > b.y() {
+        a.y() - b.y()
+    } else {
+        b.y() - a.y()
+    };
+    (dx, dy)
+}
+
+fn walk_back(
+    came_from: &HashMap<(usize, usize), (usize, usize)>,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let mut cells = vec![goal];
+    let mut current = goal;
+    while current != start {
+        current = came_from[&current];
+        cells.push(current);
+    }
+    cells.reverse();
+    cells
+}
+
+fn is_passable<P: Fn(char) -> bool>(grid: &Grid, c: (usize, usize), passable: &P) -> bool {
+    grid.get(c).is_some_and(|&v| passable(v))
+}
+
+// Every step costs 1, so the first time the goal is reached is the shortest.
+pub fn bfs<P: Fn(char) -> bool>(
+    grid: &Grid,
+    start: (usize, usize),
+    goal: (usize, usize),
+    neighbourhood: Neighbourhood,
+    passable: P,
+) -> Option<Path> {
+    if !is_passable(grid, start, &passable) {
+        return None;
+    }
+    let mut came_from = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    came_from.insert(start, start);
+
+    while let Some(current) = queue.pop_front() {
+        if current == goal {
+            let cells = walk_back(&came_from, start, goal);
+            return Some(Path {
+                cost: cells.len() - 1,
+                cells,
+            });
+        }
+        for next in neighbourhood.of(grid, current) {
+            if !came_from.contains_key(&next) && is_passable(grid, next, &passable) {
+                came_from.insert(next, current);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+// Only steps into `passable` cells are considered, `cost` returns the price
+// of each of them or `None` when that particular step is not allowed.
+pub fn dijkstra<P, C>(
+    grid: &Grid,
+    start: (usize, usize),
+    goal: (usize, usize),
+    neighbourhood: Neighbourhood,
+    passable: P,
+    cost: C,
+) -> Option<Path>
+where
+    P: Fn(char) -> bool,
+    C: Fn(&Grid, (usize, usize), (usize, usize)) -> Option<usize>,
+{
+    a_star(grid, start, goal, neighbourhood, passable, cost, |_| 0)
+}
+
+// `heuristic` must never overestimate the remaining cost to the goal.
+pub fn a_star<P, C, H>(
+    grid: &Grid,
+    start: (usize, usize),
+    goal: (usize, usize),
+    neighbourhood: Neighbourhood,
+    passable: P,
+    cost: C,
+    heuristic: H,
+) -> Option<Path>
+where
+    P: Fn(char) -> bool,
+    C: Fn(&Grid, (usize, usize), (usize, usize)) -> Option<usize>,
+    H: Fn((usize, usize)) -> usize,
+{
+    if !is_passable(grid, start, &passable) {
+        return None;
+    }
+    let mut came_from = HashMap::new();
+    let mut best = HashMap::new();
+    let mut open = BinaryHeap::new();
+    best.insert(start, 0);
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_, distance, current))) = open.pop() {
+        if current == goal {
+            return Some(Path {
+                cells: walk_back(&came_from, start, goal),
+                cost: distance,
+            });
+        }
+        if distance > best[&current] {
+            continue;
+        }
+        for next in neighbourhood.of(grid, current) {
+            if !is_passable(grid, next, &passable) {
+                continue;
+            }
+            let step = match cost(grid, current, next) {
+                Some(step) => step,
+                None => continue,
+            };
+            let candidate = distance + step;
+            if best.get(&next).is_none_or(|&d| candidate < d) {
+                best.insert(next, candidate);
+                came_from.insert(next, current);
+                open.push(Reverse((candidate + heuristic(next), candidate, next)));
+            }
+        }
+    }
+    None
+}
+
+// Cells reachable from `start`, in the order they were discovered.
+pub fn flood_fill<P: Fn(char) -> bool>(
+    grid: &Grid,
+    start: (usize, usize),
+    neighbourhood: Neighbourhood,
+    passable: P,
+) -> Vec<(usize, usize)> {
+    let mut seen = vec![false; grid.w * grid.h];
+    fill(grid, start, neighbourhood, &passable, &mut seen)
+}
+
+fn fill<P: Fn(char) -> bool>(
+    grid: &Grid,
+    start: (usize, usize),
+    neighbourhood: Neighbourhood,
+    passable: &P,
+    seen: &mut [bool],
+) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    if !is_passable(grid, start, passable) || seen[start.0 + start.1 * grid.w] {
+        return result;
+    }
+    let mut queue = VecDeque::new();
+    seen[start.0 + start.1 * grid.w] = true;
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        result.push(current);
+        for next in neighbourhood.of(grid, current) {
+            let i = next.0 + next.1 * grid.w;
+            if !seen[i] && is_passable(grid, next, passable) {
+                seen[i] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Components {
+    w: usize,
+    labels: Vec<Option<usize>>,
+    pub count: usize,
+}
+
+impl Components {
+    pub fn label(&self, c: impl Coord) -> Option<usize> {
+        self.labels[c.x() + c.y() * self.w]
+    }
+
+    pub fn members(&self, label: usize) -> Vec<(usize, usize)> {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(|&(_, &l)| l == Some(label))
+            .map(|(i, _)| (i % self.w, i / self.w))
+            .collect()
+    }
+}
+
+// Labels are assigned in reading order of each component's first cell.
+pub fn connected_components<P: Fn(char) -> bool>(
+    grid: &Grid,
+    neighbourhood: Neighbourhood,
+    passable: P,
+) -> Components {
+    let mut seen = vec![false; grid.w * grid.h];
+    let mut labels = vec![None; grid.w * grid.h];
+    let mut count = 0;
+
+    for (coord, _) in grid.iter() {
+        let component = fill(grid, coord, neighbourhood, &passable, &mut seen);
+        if component.is_empty() {
+            continue;
+        }
+        for (x, y) in component {
+            labels[x + y * grid.w] = Some(count);
+        }
+        count += 1;
+    }
+
+    Components {
+        w: grid.w,
+        labels,
+        count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_maze() -> Grid {
+        "S.#.....\n\
+         .##.###.\n\
+         ...#..#.\n\
+         .#...#..\n\
+         .#.#...E"
+            .parse()
+            .unwrap()
+    }
+
+    fn open(c: char) -> bool {
+        c != '#'
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let maze = test_maze();
+        let (start, goal) = (maze.find('S').unwrap(), maze.find('E').unwrap());
+
+        let path = bfs(&maze, start, goal, Neighbourhood::VonNeumann, open).unwrap();
+        assert_eq!(11, path.cost);
+        assert_eq!(Some(&start), path.cells.first());
+        assert_eq!(Some(&goal), path.cells.last());
+
+        let unit = |_: &Grid, _, _| Some(1);
+        let path = dijkstra(&maze, start, goal, Neighbourhood::VonNeumann, open, unit).unwrap();
+        assert_eq!(11, path.cost);
+        let heuristic = |c| manhattan(c, goal);
+        let path = a_star(
+            &maze,
+            start,
+            goal,
+            Neighbourhood::VonNeumann,
+            open,
+            unit,
+            heuristic,
+        );
+        assert_eq!(11, path.unwrap().cost);
+
+        // Starting inside a wall finds nothing, whichever search is used.
+        let wall = (2, 0);
+        assert_eq!(
+            None,
+            bfs(&maze, wall, goal, Neighbourhood::VonNeumann, open)
+        );
+        assert_eq!(
+            None,
+            dijkstra(&maze, wall, goal, Neighbourhood::VonNeumann, open, unit)
+        );
+        assert_eq!(
+            None,
+            a_star(
+                &maze,
+                wall,
+                goal,
+                Neighbourhood::VonNeumann,
+                open,
+                unit,
+                heuristic
+            )
+        );
+
+        let path = bfs(&maze, start, goal, Neighbourhood::Moore, open).unwrap();
+        assert_eq!(8, path.cost);
+        assert!(path
+            .cells
+            .windows(2)
+            .all(|step| chebyshev(step[0], step[1]) == 1));
+
+        assert_eq!(None, bfs(&maze, start, (2, 0), Neighbourhood::Moore, open));
+    }
+
+    #[test]
+    fn test_weighted_paths() {
+        let costs: Grid = "111\n991\n111".parse().unwrap();
+        let cost = |g: &Grid, _, to| g.get(to).and_then(|c| c.to_digit(10)).map(|c| c as usize);
+        let any = |_| true;
+
+        let path = dijkstra(&costs, (0, 0), (0, 2), Neighbourhood::VonNeumann, any, cost).unwrap();
+        assert_eq!(6, path.cost);
+        assert_eq!(7, path.cells.len());
+    }
+
+    #[test]
+    fn test_components() {
+        let maze = test_maze();
+        let walls = |c| c == '#';
+
+        assert_eq!(
+            28,
+            flood_fill(&maze, (4, 0), Neighbourhood::VonNeumann, open).len()
+        );
+        assert!(flood_fill(&maze, (2, 0), Neighbourhood::VonNeumann, open).is_empty());
+
+        let components = connected_components(&maze, Neighbourhood::VonNeumann, walls);
+        assert_eq!(6, components.count);
+        assert_eq!(Some(0), components.label((2, 0)));
+        assert_eq!(None, components.label((0, 0)));
+        assert_eq!(vec![(2, 0), (1, 1), (2, 1)], components.members(0));
+
+        let components = connected_components(&maze, Neighbourhood::Moore, walls);
+        assert_eq!(3, components.count);
+    }
+}
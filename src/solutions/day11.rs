@@ -1,5 +1,5 @@
-use crate::grid::Grid;
-use crate::solver::Solver;
+use crate::grid::{Grid, Simulation};
+use crate::solver::{Solver, Visualize};
 use std::io::{self};
 
 pub struct Problem;
@@ -14,44 +14,62 @@ impl Solver for Problem {
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output {
         let adjacency = |g: &Grid, coord: (usize, usize)| g.count_adjacents(coord, '#');
-        process_until_balance(input.clone(), adjacency, 5).count_type('#')
+        let simulation =
+            seat_simulation(input.clone(), adjacency, 5).track_changes(Grid::adjacent_coords);
+        process_until_balance(simulation).count_type('#')
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output {
-        let adjacency = |g: &Grid, coord: (usize, usize)| g.count_line_of_sight(&coord, '#');
-        process_until_balance(input.clone(), adjacency, 5).count_type('#')
+        let table = input.sight_table();
+        let adjacency = |g: &Grid, coord: (usize, usize)| g.count_visible(&table, coord, '#');
+        let simulation = seat_simulation(input.clone(), adjacency, 5)
+            .track_changes(|_, coord| table.visible_coords(coord));
+        process_until_balance(simulation).count_type('#')
     }
 }
 
-fn process_until_balance<F: Fn(&Grid, (usize, usize)) -> usize>(
-    mut grid: Grid,
-    adjacents_fn: F,
-    tolerance: usize,
-) -> Grid {
-    loop {
-        let mut changed = false;
-        let grid_snapshot = grid.clone();
+impl Visualize for Problem {
+    fn visualize(&self, input: &Self::Input, frame: &mut dyn FnMut(&str)) {
+        each_generation(input, |grid| frame(&grid.to_string()));
+    }
+}
 
-        for x in 0..grid.w {
-            for y in 0..grid.h {
-                let current = *grid_snapshot.get((x, y)).unwrap();
-                if current == '.' {
-                    continue;
-                }
-                let adjacents = adjacents_fn(&grid_snapshot, (x, y));
-                if let (new, true) = run_step(current, adjacents, tolerance) {
-                    grid.set((x, y), new);
-                    changed = true
-                }
-            }
-        }
+// Every seat layout from the input until it settles, using the part 1 rules.
+pub fn generations(input: &Grid) -> Vec<Grid> {
+    let mut result = Vec::new();
+    each_generation(input, |grid| result.push(grid.clone()));
+    result
+}
 
-        if !changed {
-            break;
-        };
+fn each_generation<F: FnMut(&Grid)>(input: &Grid, mut f: F) {
+    let adjacency = |g: &Grid, coord: (usize, usize)| g.count_adjacents(coord, '#');
+    let mut simulation =
+        seat_simulation(input.clone(), adjacency, 5).track_changes(Grid::adjacent_coords);
+
+    f(simulation.grid());
+    while !simulation.step().is_empty() {
+        f(simulation.grid());
     }
+}
+
+fn seat_simulation<F: Fn(&Grid, (usize, usize)) -> usize>(
+    grid: Grid,
+    adjacents_fn: F,
+    tolerance: usize,
+) -> Simulation<impl Fn(&Grid, (usize, usize), char) -> char> {
+    Simulation::new(grid, move |g: &Grid, coord, current| {
+        if current == '.' {
+            return current;
+        }
+        run_step(current, adjacents_fn(g, coord), tolerance).0
+    })
+}
 
-    grid
+fn process_until_balance<F: Fn(&Grid, (usize, usize), char) -> char>(
+    mut simulation: Simulation<F>,
+) -> Grid {
+    simulation.run();
+    simulation.into_grid()
 }
 
 fn run_step(current: char, adjacents: usize, occupied_tolerance: usize) -> (char, bool) {
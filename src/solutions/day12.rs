@@ -1,6 +1,6 @@
 use io::Result;
 
-use crate::solver::Solver;
+use crate::solver::{Solver, Visualize};
 use std::io::{self, BufRead, BufReader};
 
 use lazy_static::lazy_static;
@@ -75,6 +75,71 @@ impl Solver for Problem {
     }
 }
 
+impl Visualize for Problem {
+    fn visualize(&self, input: &Self::Input, frame: &mut dyn FnMut(&str)) {
+        let mut route = vec![(0, 0)];
+        let initial_location = Location {
+            position: (0, 0),
+            bearing: Action::East(0),
+        };
+        input.iter().fold(initial_location, |location, action| {
+            let next = process_movement(location, action);
+            route.push(next.position);
+            next
+        });
+
+        for step in 1..=route.len() {
+            frame(&render_route(&route, step));
+        }
+    }
+}
+
+// Squeezes the whole route into a fixed size canvas so every frame has the
+// same scale. Part 1 positions grow westwards, so x is mirrored to put east on
+// the right.
+fn render_route(route: &[(i32, i32)], step: usize) -> String {
+    const COLS: i32 = 78;
+    const ROWS: i32 = 36;
+    let (min_x, max_x) = (
+        route.iter().map(|p| -p.0).min().unwrap(),
+        route.iter().map(|p| -p.0).max().unwrap(),
+    );
+    let (min_y, max_y) = (
+        route.iter().map(|p| p.1).min().unwrap(),
+        route.iter().map(|p| p.1).max().unwrap(),
+    );
+    let scale_x = (max_x - min_x) / COLS + 1;
+    let scale_y = (max_y - min_y) / ROWS + 1;
+    let to_cell = |p: &(i32, i32)| ((-p.0 - min_x) / scale_x, (max_y - p.1) / scale_y);
+
+    let mut canvas = vec![vec![' '; COLS as usize + 1]; ROWS as usize + 1];
+    for leg in route[..step].windows(2) {
+        let ((c0, r0), (c1, r1)) = (to_cell(&leg[0]), to_cell(&leg[1]));
+        let cells = (c1 - c0).abs().max((r1 - r0).abs()).max(1);
+        for i in 0..=cells {
+            let col = c0 + (c1 - c0) * i / cells;
+            let row = r0 + (r1 - r0) * i / cells;
+            canvas[row as usize][col as usize] = '.';
+        }
+    }
+    let (col, row) = to_cell(&route[0]);
+    canvas[row as usize][col as usize] = 'o';
+    let (col, row) = to_cell(&route[step - 1]);
+    canvas[row as usize][col as usize] = '@';
+
+    let mut result: String = canvas
+        .iter()
+        .map(|row| row.iter().collect::<String>().trim_end().to_owned() + "\n")
+        .collect();
+    let position = route[step - 1];
+    result.push_str(&format!(
+        "position: {:?}, distance: {}\n",
+        position,
+        position.0.abs() + position.1.abs()
+    ));
+    result
+}
+
 fn process_movement(initial: Location, action: &Action) -> Location {
     match action {
         Action::North(val) => Location {
@@ -171,3 +236,41 @@ fn turn_waypoint(initial: (i32, i32), degrees: i32) -> (i32, i32) {
         _ => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_route() {
+        // East ten, then north four.
+        let route = [(0, 0), (-10, 0), (-10, 4)];
+        let frame = render_route(&route, 3);
+        let lines: Vec<&str> = frame.lines().collect();
+        assert_eq!(38, lines.len());
+        assert_eq!("          @", lines[0]);
+        assert_eq!("          .", lines[3]);
+        assert_eq!("o..........", lines[4]);
+        assert!(lines[5..37].iter().all(|l| l.is_empty()));
+        assert_eq!("position: (-10, 4), distance: 14", lines[37]);
+
+        let lines: Vec<String> = render_route(&route, 2).lines().map(String::from).collect();
+        assert_eq!("", lines[0]);
+        assert_eq!("o.........@", lines[4]);
+    }
+
+    #[test]
+    fn test_render_route_mirrors_and_scales() {
+        // Westwards is positive x, so the start ends up on the right.
+        let frame = render_route(&[(0, 0), (5, 0)], 2);
+        assert_eq!(Some("@....o"), frame.lines().next());
+
+        let route = [(0, 0), (-1000, 0), (-1000, -500)];
+        let frame = render_route(&route, 3);
+        let lines: Vec<&str> = frame.lines().collect();
+        assert_eq!(38, lines.len());
+        assert!(lines[..37].iter().all(|l| l.len() <= 79));
+        assert!(lines[0].starts_with('o'));
+        assert_eq!(Some(76), lines[35].find('@'));
+    }
+}
@@ -1,16 +1,19 @@
+use crate::bitgrid::{BitGrid, Rule};
+use crate::solver::{Solver, Visualize};
 use io::Result;
-use itertools::iproduct;
-
-use crate::solver::Solver;
 use std::{
     collections::HashSet,
     io::{self, BufRead, BufReader},
 };
 
+const CYCLES: usize = 6;
+
+pub type Cubes = HashSet<(isize, isize, isize, isize)>;
+
 pub struct Problem;
 
 impl Solver for Problem {
-    type Input = HashSet<(isize, isize, isize, isize)>;
+    type Input = Cubes;
     type Output = usize;
 
     fn parse_input<R: io::Read + io::Seek>(&self, r: R) -> Self::Input {
@@ -23,129 +26,116 @@ impl Solver for Problem {
                     .enumerate()
                     .filter(|(_x, c)| *c == '#')
                     .map(|(x, _c)| (x as isize, y as isize, 0, 0))
-                    .collect::<HashSet<(isize, isize, isize, isize)>>()
+                    .collect::<Cubes>()
+            })
+            .fold(Cubes::new(), |acc, current| {
+                acc.union(&current).map(|x| *x).collect::<Cubes>()
             })
-            .fold(
-                HashSet::<(isize, isize, isize, isize)>::new(),
-                |acc, current| {
-                    acc.union(&current)
-                        .map(|x| *x)
-                        .collect::<HashSet<(isize, isize, isize, isize)>>()
-                },
-            )
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output {
-        let init_height = input
-            .iter()
-            .max_by(|&one, &other| one.1.cmp(&other.1))
-            .unwrap()
-            .1 as isize;
-        let init_width = input
-            .iter()
-            .max_by(|&one, &other| one.0.cmp(&other.0))
-            .unwrap()
-            .0 as isize;
-
-        let mut grid = input.clone();
-        for cycle in 1isize..=6 {
-            let mut new_grid = HashSet::<(isize, isize, isize, isize)>::new();
-            for (z, y, x) in iproduct!(
-                -cycle..=cycle,
-                -cycle..=init_height + cycle,
-                -cycle..=init_width + cycle
-            ) {
-                let count = iproduct!(x - 1..=x + 1, y - 1..=y + 1, z - 1..=z + 1)
-                    .filter(|&(i, j, k)| {
-                        grid.contains(&(i, j, k, 0)) && (i, j, k, 0) != (x, y, z, 0)
-                    })
-                    .count();
-                if (2..=3).contains(&count) && grid.contains(&(x, y, z, 0)) {
-                    new_grid.insert((x, y, z, 0));
-                } else if count == 3 && !grid.contains(&(x, y, z, 0)) {
-                    new_grid.insert((x, y, z, 0));
-                }
-            }
-            grid = new_grid.clone();
-        }
-        grid.iter().count()
+        generations(input, false).last().unwrap().iter().count()
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output {
-        let init_height = input
-            .iter()
-            .max_by(|&one, &other| one.1.cmp(&other.1))
-            .unwrap()
-            .1 as isize;
-        let init_width = input
-            .iter()
-            .max_by(|&one, &other| one.0.cmp(&other.0))
-            .unwrap()
-            .0 as isize;
-
-        let mut grid = input.clone();
-        for cycle in 1isize..=6 {
-            let mut new_grid = HashSet::<(isize, isize, isize, isize)>::new();
-            for (w, z, y, x) in iproduct!(
-                -cycle..=cycle,
-                -cycle..=cycle,
-                -cycle..=init_height + cycle,
-                -cycle..=init_width + cycle
-            ) {
-                let count = iproduct!(x - 1..=x + 1, y - 1..=y + 1, z - 1..=z + 1, w - 1..=w + 1)
-                    .filter(|&(i, j, k, l)| {
-                        grid.contains(&(i, j, k, l)) && (i, j, k, l) != (x, y, z, w)
-                    })
-                    .count();
-                if (2..=3).contains(&count) && grid.contains(&(x, y, z, w)) {
-                    new_grid.insert((x, y, z, w));
-                } else if count == 3 && !grid.contains(&(x, y, z, w)) {
-                    new_grid.insert((x, y, z, w));
-                }
-            }
-            grid = new_grid.clone();
+        generations(input, true).last().unwrap().iter().count()
+    }
+}
+
+impl Visualize for Problem {
+    fn visualize(&self, input: &Self::Input, frame: &mut dyn FnMut(&str)) {
+        for grid in generations(input, false).iter() {
+            frame(&render(grid));
         }
-        grid.iter().count()
     }
 }
 
-#[allow(dead_code)]
-fn print_grid(grid: &HashSet<(isize, isize, isize, isize)>) {
-    let init_height = grid
+// The initial state followed by the six boot cycles.
+pub fn generations(input: &Cubes, four_d: bool) -> Vec<Cubes> {
+    let init_height = input
         .iter()
         .max_by(|&one, &other| one.1.cmp(&other.1))
         .unwrap()
-        .1 as isize;
-    let init_width = grid
+        .1 as usize;
+    let init_width = input
         .iter()
         .max_by(|&one, &other| one.0.cmp(&other.0))
         .unwrap()
-        .0 as isize;
-    let init_depth = grid
-        .iter()
-        .max_by(|&one, &other| one.2.cmp(&other.2))
-        .unwrap()
-        .2 as isize;
+        .0 as usize;
 
-    let init_w = grid
-        .iter()
-        .max_by(|&one, &other| one.3.cmp(&other.3))
-        .unwrap()
-        .3 as isize;
-
-    for z in -init_depth..=init_depth {
-        for w in -init_w..=init_w {
-            println!("Z = {} W = {}", z, w);
-            for y in -init_height..=init_height {
-                for x in -init_width..=init_width {
-                    if grid.contains(&(x, y, z, w)) {
-                        print!("#");
-                    } else {
-                        print!(".");
-                    }
-                }
-                println!("");
+    // Active cells spread at most one cell per cycle, so a margin of CYCLES
+    // around the initial slice holds everything that can ever become active.
+    let layers = 2 * CYCLES + 1;
+    let mut space = BitGrid::with_dims([
+        init_width + layers,
+        init_height + layers,
+        layers,
+        if four_d { layers } else { 1 },
+    ]);
+    let offset = |v: isize| (v + CYCLES as isize) as usize;
+    let w_offset = |v: isize| if four_d { offset(v) } else { v as usize };
+    for &(x, y, z, w) in input.iter() {
+        space.set_at([offset(x), offset(y), offset(z), w_offset(w)], true);
+    }
+
+    let mut result = vec![input.clone()];
+    for _ in 0..CYCLES {
+        space = space.step(&Rule::conway());
+        result.push(
+            space
+                .active()
+                .map(|[x, y, z, w]| {
+                    let back = |v: usize| v as isize - CYCLES as isize;
+                    let w = if four_d { back(w) } else { w as isize };
+                    (back(x), back(y), back(z), w)
+                })
+                .collect(),
+        );
+    }
+    result
+}
+
+// Every z layer of the 3D pocket dimension, top to bottom.
+fn render(grid: &Cubes) -> String {
+    let bounds = |axis: fn(&(isize, isize, isize, isize)) -> isize| {
+        let min = grid.iter().map(axis).min().unwrap_or(0);
+        let max = grid.iter().map(axis).max().unwrap_or(0);
+        min..=max
+    };
+    let (xs, ys, zs) = (bounds(|c| c.0), bounds(|c| c.1), bounds(|c| c.2));
+
+    let mut result = String::new();
+    for z in zs {
+        result.push_str(&format!("z={}\n", z));
+        for y in ys.clone() {
+            for x in xs.clone() {
+                result.push(if grid.contains(&(x, y, z, 0)) {
+                    '#'
+                } else {
+                    '.'
+                });
             }
+            result.push('\n');
         }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let cubes: Cubes = [(0, 0, 0, 0), (1, 1, 0, 0), (1, 1, 1, 0)]
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!("z=0\n#.\n.#\n\nz=1\n..\n.#\n\n", render(&cubes));
+
+        let shifted: Cubes = [(-1, 2, -3, 0)].iter().copied().collect();
+        assert_eq!("z=-3\n#\n\n", render(&shifted));
+        assert_eq!("z=0\n.\n\n", render(&Cubes::new()));
     }
 }
@@ -1,98 +1,59 @@
-use io::Result;
-
+use crate::expr::{parse, tokenize, Precedence, Token};
 use crate::solver::Solver;
 use std::io::{self, BufRead, BufReader};
 
 pub struct Problem;
 
+fn sum_with(input: &[(usize, Vec<Token>)], precedence: &Precedence) -> i64 {
+    input.iter().fold(0, |sum, (line, tokens)| {
+        let value = parse(tokens, precedence)
+            .and_then(|expr| expr.eval())
+            .unwrap_or_else(|e| panic!("line {}: {}", line, e));
+        sum.checked_add(value)
+            .unwrap_or_else(|| panic!("line {}: the sum overflows", line))
+    })
+}
+
 impl Solver for Problem {
-    type Input = Vec<Vec<char>>;
-    type Output = usize;
+    // Each expression with its line number, blank lines are skipped.
+    type Input = Vec<(usize, Vec<Token>)>;
+    type Output = i64;
 
     fn parse_input<R: io::Read + io::Seek>(&self, r: R) -> Self::Input {
         BufReader::new(r)
             .lines()
             .filter_map(Result::ok)
-            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect())
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let tokens = tokenize(&line).unwrap_or_else(|e| panic!("line {}: {}", i + 1, e));
+                (i + 1, tokens)
+            })
             .collect()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output {
-        input.iter().map(|x| eval_p1(&x[..])).sum()
+        sum_with(input, &Precedence::flat())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output {
-        input.iter().map(|x| eval_p2(&x[..])).sum()
-    }
-}
-
-fn find_matching_parenthesis(s: &[char]) -> usize {
-    let mut offset = 0;
-    for (i, &c) in s.iter().enumerate() {
-        match c {
-            '(' => offset += 1,
-            ')' => offset -= 1,
-            _ => {}
-        }
-        if offset == 0 {
-            return i;
-        }
-    }
-    unreachable!()
-}
-
-fn eval_p1(s: &[char]) -> usize {
-    let (mut val, mut i) = (0, 0);
-    let mut op = '+';
-    while i < s.len() {
-        match s[i] {
-            '+' => op = '+',
-            '*' => op = '*',
-            '0'..='9' => match op {
-                '+' => val += s[i].to_digit(10).unwrap() as usize,
-                '*' => val *= s[i].to_digit(10).unwrap() as usize,
-                _ => unreachable!(),
-            },
-            '(' => {
-                let end = i + find_matching_parenthesis(&s[i..]);
-                let v = eval_p1(&s[(i + 1)..end]);
-                match op {
-                    '+' => val += v,
-                    '*' => val *= v,
-                    _ => unreachable!(),
-                }
-                i = end;
-            }
-            _ => unreachable!(),
-        }
-        i += 1;
+        sum_with(input, &Precedence::addition_first())
     }
-    val
 }
 
-fn eval_term(s: &[char]) -> (usize, usize) {
-    if s[0] == '(' {
-        let j = find_matching_parenthesis(s);
-        (eval_p2(&s[1..j]), j)
-    } else {
-        (s[0].to_digit(10).unwrap() as usize, 0)
-    }
-}
-
-fn eval_p2(s: &[char]) -> usize {
-    let (mut val, mut i) = (1, 0);
-    while i < s.len() {
-        let (mut v, step) = eval_term(&s[i..]);
-        i += step;
-
-        while let Some('+') = s.get(i + 1) {
-            let (tmp, step) = eval_term(&s[(i + 2)..]);
-            v += tmp;
-            i += step + 2;
-        }
-
-        val *= v;
-        i += 2;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_blank_lines() {
+        let input = Problem.parse_input(Cursor::new("1 + 2 * 3\n\n  \n2 * 3 + (4 * 5)\n"));
+        assert_eq!(
+            vec![1, 4],
+            input.iter().map(|(line, _)| *line).collect::<Vec<_>>()
+        );
+        assert_eq!(9 + 26, Problem.solve_first(&input));
+        assert_eq!(9 + 46, Problem.solve_second(&input));
     }
-    val
 }
@@ -14,29 +14,22 @@ impl Solver for Problem {
         let mut buffer = String::new();
         buf_r.read_to_string(&mut buffer).unwrap();
 
-        buffer.split("\n\n").map(|s| String::from(s)).collect()
+        split_batch(&buffer).map(String::from).collect()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output {
         input
             .into_iter()
-            .filter(|&pass| passport_has_fields(pass))
+            .filter_map(|pass| pass.parse::<Record>().ok())
+            .filter(Record::has_required_fields)
             .count()
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output {
         input
             .into_iter()
-            .filter(|&pass| pass.parse::<Passport>().is_ok())
+            .filter_map(|pass| pass.parse::<Record>().ok())
+            .filter(|record| record.validate().is_ok())
             .count()
     }
 }
-
-fn passport_has_fields(pass: &str) -> bool {
-    let required_fields = vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-    required_fields
-        .iter()
-        .filter(|&field| pass.contains(field))
-        .count()
-        == required_fields.len()
-}
@@ -17,8 +17,10 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+use crate::export::{sparse_slice, Animation, Bounds, Image, Palette};
 use crate::solver::Solver;
-use std::fs::File;
+use crate::visualize::Terminal;
+use std::{fs::File, path::Path};
 
 fn load_day(day: u32) -> File {
     let path = format!("inputs/day{}.txt", day);
@@ -54,3 +56,72 @@ pub fn solve(day: u32) {
         d => println!("Day {} has not been solved yet", d),
     }
 }
+
+pub fn export(day: u32, path: &str) {
+    let day_file = load_day(day);
+    let palette = Palette::default();
+    let mut animation = Animation::new(20);
+    match day {
+        11 => {
+            let input = day11::Problem {}.parse_input(day_file);
+            for grid in day11::generations(&input) {
+                animation.push(Image::from_grid(&grid, &palette));
+            }
+        }
+        17 => {
+            let input = day17::Problem {}.parse_input(day_file);
+            let initial = match Bounds::of(&input) {
+                Some(bounds) => bounds,
+                None => {
+                    println!("Day 17 has no active cubes to export");
+                    return;
+                }
+            };
+            let generations = day17::generations(&input, false);
+            let bounds = generations
+                .iter()
+                .filter_map(Bounds::of)
+                .fold(initial, |acc, b| acc.union(&b));
+            for cubes in generations.iter() {
+                let grid = sparse_slice(cubes, 0, 0, &bounds);
+                animation.push(Image::from_grid(&grid, &palette));
+            }
+        }
+        d => {
+            println!("Day {} has nothing to export", d);
+            return;
+        }
+    }
+
+    match animation.save(Path::new(path)) {
+        Ok(()) => println!("Wrote {} frames to {}", animation.len(), path),
+        Err(e) => println!("couldn't export to {}: {}", path, e),
+    }
+}
+
+pub fn visualize(day: u32, fps: u32) {
+    let day_file = load_day(day);
+    let mut terminal = Terminal::new(fps);
+    let result = match day {
+        11 => {
+            let problem = day11::Problem {};
+            terminal.play(&problem, &problem.parse_input(day_file))
+        }
+        12 => {
+            let problem = day12::Problem {};
+            terminal.play(&problem, &problem.parse_input(day_file))
+        }
+        17 => {
+            let problem = day17::Problem {};
+            terminal.play(&problem, &problem.parse_input(day_file))
+        }
+        d => {
+            println!("Day {} has no visualization", d);
+            return;
+        }
+    };
+
+    if let Err(e) = result {
+        println!("couldn't draw day {}: {}", day, e);
+    }
+}
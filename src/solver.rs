@@ -32,3 +32,9 @@ pub trait Solver {
         println!("Solution 2: {}", s2);
     }
 }
+
+// Simulation days implement this to have their intermediate states drawn,
+// `frame` is called with the rendered state after every step.
+pub trait Visualize: Solver {
+    fn visualize(&self, input: &Self::Input, frame: &mut dyn FnMut(&str));
+}
@@ -0,0 +1,118 @@
+use crate::solver::Visualize;
+use std::{
+    io::{self, Stdout, Write},
+    thread,
+    time::Duration,
+};
+
+const CLEAR: &str = "\x1b[2J";
+const HOME: &str = "\x1b[H";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+pub struct Terminal<W = Stdout> {
+    out: W,
+    delay: Duration,
+    frames: usize,
+}
+
+impl Terminal {
+    pub fn new(fps: u32) -> Self {
+        Terminal::with_writer(io::stdout(), fps)
+    }
+}
+
+impl<W: Write> Terminal<W> {
+    pub fn with_writer(out: W, fps: u32) -> Self {
+        Terminal {
+            out,
+            delay: Duration::from_secs(1) / fps.max(1),
+            frames: 0,
+        }
+    }
+
+    // Redraws from the top left corner instead of scrolling.
+    pub fn draw(&mut self, frame: &str) -> io::Result<()> {
+        write!(self.out, "{}{}", HOME, CLEAR)?;
+        writeln!(self.out, "step {}", self.frames)?;
+        write!(self.out, "{}", frame)?;
+        self.out.flush()?;
+        self.frames += 1;
+
+        thread::sleep(self.delay);
+        Ok(())
+    }
+
+    pub fn play<V: Visualize>(&mut self, solver: &V, input: &V::Input) -> io::Result<()> {
+        write!(self.out, "{}", HIDE_CURSOR)?;
+        let mut result = Ok(());
+        solver.visualize(input, &mut |frame| {
+            if result.is_ok() {
+                result = self.draw(frame);
+            }
+        });
+        write!(self.out, "{}", SHOW_CURSOR)?;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+
+    struct Countdown;
+
+    impl Solver for Countdown {
+        type Input = usize;
+        type Output = usize;
+
+        fn parse_input<R: io::Read + io::Seek>(&self, _r: R) -> Self::Input {
+            3
+        }
+
+        fn solve_first(&self, input: &Self::Input) -> Self::Output {
+            *input
+        }
+
+        fn solve_second(&self, input: &Self::Input) -> Self::Output {
+            *input
+        }
+    }
+
+    impl Visualize for Countdown {
+        fn visualize(&self, input: &Self::Input, frame: &mut dyn FnMut(&str)) {
+            for i in (1..=*input).rev() {
+                frame(&format!("{}\n", i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut out = Vec::new();
+        let mut terminal = Terminal::with_writer(&mut out, 1000);
+        terminal.draw("ab\ncd\n").unwrap();
+        terminal.draw("ef\n").unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            "\x1b[H\x1b[2Jstep 0\nab\ncd\n\x1b[H\x1b[2Jstep 1\nef\n",
+            out
+        );
+    }
+
+    #[test]
+    fn test_play() {
+        let mut out = Vec::new();
+        let mut terminal = Terminal::with_writer(&mut out, 1000);
+        terminal.play(&Countdown, &3).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(HIDE_CURSOR));
+        assert!(out.ends_with(SHOW_CURSOR));
+        let frames: Vec<&str> = out.split(HOME).skip(1).collect();
+        assert_eq!(3, frames.len());
+        assert_eq!("\x1b[2Jstep 2\n1\n\x1b[?25h", frames[2]);
+    }
+}
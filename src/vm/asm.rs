@@ -0,0 +1,209 @@
+use super::{Instruction, Program};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+const OPCODES: [&str; 3] = ["nop", "acc", "jmp"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnknownOpcode(String),
+    MissingOperand,
+    BadOperand(String),
+    TrailingInput(String),
+    BadLabel(String),
+    DuplicateLabel(String),
+    UnknownLabel(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsmError {
+    // Source line of the bad instruction, counted from 1.
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ErrorKind::UnknownOpcode(op) => write!(f, "unknown opcode {:?}", op),
+            ErrorKind::MissingOperand => write!(f, "missing operand"),
+            ErrorKind::BadOperand(operand) => write!(f, "bad operand {:?}", operand),
+            ErrorKind::TrailingInput(s) => write!(f, "unexpected {:?} after operand", s),
+            ErrorKind::BadLabel(label) => write!(f, "bad label name {:?}", label),
+            ErrorKind::DuplicateLabel(label) => write!(f, "label {} defined twice", label),
+            ErrorKind::UnknownLabel(label) => write!(f, "unknown label {}", label),
+        }
+    }
+}
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// One `op operand` per line. `#` and `;` start comments, `name:` labels the
+// next instruction and `jmp`/`nop` may use a label instead of an offset.
+pub fn assemble(source: &str) -> Result<Program, AsmError> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut statements: Vec<(usize, &str, &str)> = Vec::new();
+
+    for (i, raw) in source.lines().enumerate() {
+        let line = i + 1;
+        let error = |kind| AsmError { line, kind };
+        let mut code = raw.split(['#', ';']).next().unwrap().trim();
+
+        if let Some(colon) = code.find(':') {
+            let label = code[..colon].trim();
+            if !is_label(label) {
+                return Err(error(ErrorKind::BadLabel(label.to_owned())));
+            }
+            if labels.insert(label, statements.len()).is_some() {
+                return Err(error(ErrorKind::DuplicateLabel(label.to_owned())));
+            }
+            code = code[colon + 1..].trim();
+        }
+
+        let words: Vec<&str> = code.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {}
+            [op, ..] if !OPCODES.contains(op) => {
+                return Err(error(ErrorKind::UnknownOpcode(op.to_string())))
+            }
+            [_] => return Err(error(ErrorKind::MissingOperand)),
+            [op, operand] => statements.push((line, op, operand)),
+            [_, _, rest @ ..] => return Err(error(ErrorKind::TrailingInput(rest.join(" ")))),
+        }
+    }
+
+    statements
+        .iter()
+        .enumerate()
+        .map(|(index, &(line, op, operand))| {
+            let error = |kind| AsmError { line, kind };
+            let value = match operand.parse::<i32>() {
+                Ok(value) => value,
+                Err(_) if op != "acc" && is_label(operand) => {
+                    let target = labels
+                        .get(operand)
+                        .ok_or_else(|| error(ErrorKind::UnknownLabel(operand.to_owned())))?;
+                    *target as i32 - index as i32
+                }
+                Err(_) => return Err(error(ErrorKind::BadOperand(operand.to_owned()))),
+            };
+            Ok(match op {
+                "nop" => Instruction::Nop(value),
+                "acc" => Instruction::Acc(value),
+                _ => Instruction::Jmp(value),
+            })
+        })
+        .collect()
+}
+
+// One canonical `op +n` line per instruction, which `assemble` reads back.
+pub fn disassemble(program: &[Instruction]) -> String {
+    program.iter().map(|i| format!("{}\n", i)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Instruction::*;
+
+    #[test]
+    fn test_assemble() {
+        assert_eq!(
+            Ok(vec![Nop(0), Acc(1), Jmp(3), Acc(-99), Jmp(4)]),
+            assemble("nop +0\nacc +1\njmp +3\nacc -99\njmp 4\n")
+        );
+
+        let source = "
+            # the day 8 example, with labels
+            nop +0
+            start: acc +1     ; 1
+            jmp fix
+            back:
+            acc +3
+            jmp start
+            acc -99
+            acc +1
+            fix: nop back
+            acc +6
+            jmp end
+            end:";
+        assert_eq!(
+            Ok(vec![
+                Nop(0),
+                Acc(1),
+                Jmp(5),
+                Acc(3),
+                Jmp(-3),
+                Acc(-99),
+                Acc(1),
+                Nop(-4),
+                Acc(6),
+                Jmp(1),
+            ]),
+            assemble(source)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |line, kind| Err(AsmError { line, kind });
+
+        assert_eq!(
+            error(2, ErrorKind::UnknownOpcode(String::from("mul"))),
+            assemble("nop +0\nmul +2")
+        );
+        assert_eq!(error(1, ErrorKind::MissingOperand), assemble("jmp"));
+        assert_eq!(
+            error(3, ErrorKind::BadOperand(String::from("+1x"))),
+            assemble("\n\nacc +1x")
+        );
+        assert_eq!(
+            error(1, ErrorKind::BadOperand(String::from("loop"))),
+            assemble("loop: acc loop")
+        );
+        assert_eq!(
+            error(1, ErrorKind::TrailingInput(String::from("+2 +3"))),
+            assemble("acc +1 +2 +3")
+        );
+        assert_eq!(
+            error(2, ErrorKind::DuplicateLabel(String::from("a"))),
+            assemble("a: nop +0\na: nop +0")
+        );
+        assert_eq!(
+            error(1, ErrorKind::BadLabel(String::from("1a"))),
+            assemble("1a: nop +0")
+        );
+        assert_eq!(
+            error(2, ErrorKind::UnknownLabel(String::from("nowhere"))),
+            assemble("nop +0\njmp nowhere")
+        );
+        assert_eq!(
+            "line 2: unknown opcode \"mul\"",
+            assemble("\nmul +1").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let program = vec![Nop(0), Acc(1), Jmp(-4), Acc(-99), Nop(12)];
+        let source = disassemble(&program);
+
+        assert_eq!("nop +0\nacc +1\njmp -4\nacc -99\nnop +12\n", source);
+        assert_eq!(Ok(program), assemble(&source));
+
+        let labelled = "top: acc 5\njmp top # again\n";
+        assert_eq!(
+            "acc +5\njmp -1\n",
+            disassemble(&assemble(labelled).unwrap())
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use super::Instruction;
+use std::{collections::VecDeque, fmt::Write};
+
+// Instruction-level control flow. Node `len` stands for normal termination,
+// jumps anywhere else outside the program have no successor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg {
+    successors: Vec<Option<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+// Where control goes after running `instruction` at `pc`.
+pub fn successor(instruction: Instruction, pc: usize, len: usize) -> Option<usize> {
+    let target = pc as isize + instruction.effect().1;
+    if target >= 0 && target as usize <= len {
+        Some(target as usize)
+    } else {
+        None
+    }
+}
+
+impl Cfg {
+    pub fn new(program: &[Instruction]) -> Self {
+        let len = program.len();
+        let successors: Vec<Option<usize>> = program
+            .iter()
+            .enumerate()
+            .map(|(pc, &instruction)| successor(instruction, pc, len))
+            .collect();
+        let mut predecessors = vec![Vec::new(); len + 1];
+        for (pc, next) in successors.iter().enumerate() {
+            if let Some(next) = next {
+                predecessors[*next].push(pc);
+            }
+        }
+        Cfg {
+            successors,
+            predecessors,
+        }
+    }
+
+    pub fn exit(&self) -> usize {
+        self.successors.len()
+    }
+
+    pub fn predecessors(&self, pc: usize) -> &[usize] {
+        &self.predecessors[pc]
+    }
+
+    // Every node with a path to `target`, found walking the edges backwards.
+    pub fn reaching(&self, target: usize) -> Vec<bool> {
+        let mut reached = vec![false; self.exit() + 1];
+        let mut queue = VecDeque::new();
+        reached[target] = true;
+        queue.push_back(target);
+        while let Some(pc) = queue.pop_front() {
+            for &previous in self.predecessors(pc) {
+                if !reached[previous] {
+                    reached[previous] = true;
+                    queue.push_back(previous);
+                }
+            }
+        }
+        reached
+    }
+}
+
+// Instructions `start..end`, only entered at `start` and only left at the end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Block(usize),
+    Exit,
+    OutOfBounds(isize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeKind {
+    Fallthrough,
+    Jump,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: Target,
+    pub kind: EdgeKind,
+}
+
+// Basic blocks of a program. Every instruction has exactly one successor, so
+// every block has exactly one outgoing edge, `edges[i]` leaving `blocks[i]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockGraph {
+    program: Vec<Instruction>,
+    pub blocks: Vec<BasicBlock>,
+    pub edges: Vec<Edge>,
+}
+
+impl BlockGraph {
+    pub fn new(program: &[Instruction]) -> Self {
+        let len = program.len();
+        let mut leaders = vec![false; len];
+        if len > 0 {
+            leaders[0] = true;
+        }
+        for (pc, &instruction) in program.iter().enumerate() {
+            if let Instruction::Jmp(_) = instruction {
+                if let Some(target) = successor(instruction, pc, len).filter(|&t| t < len) {
+                    leaders[target] = true;
+                }
+                if pc + 1 < len {
+                    leaders[pc + 1] = true;
+                }
+            }
+        }
+
+        let starts: Vec<usize> = (0..len).filter(|&pc| leaders[pc]).collect();
+        let blocks: Vec<BasicBlock> = starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| BasicBlock {
+                start,
+                end: starts.get(i + 1).copied().unwrap_or(len),
+            })
+            .collect();
+
+        let block_of = |pc: usize| starts.binary_search(&pc).unwrap();
+        let edges = blocks
+            .iter()
+            .enumerate()
+            .map(|(from, block)| {
+                let last = block.end - 1;
+                let instruction = program[last];
+                let to = match successor(instruction, last, len) {
+                    Some(pc) if pc == len => Target::Exit,
+                    Some(pc) => Target::Block(block_of(pc)),
+                    None => Target::OutOfBounds(last as isize + instruction.effect().1),
+                };
+                let kind = match instruction {
+                    Instruction::Jmp(_) => EdgeKind::Jump,
+                    _ => EdgeKind::Fallthrough,
+                };
+                Edge { from, to, kind }
+            })
+            .collect();
+
+        BlockGraph {
+            program: program.to_vec(),
+            blocks,
+            edges,
+        }
+    }
+
+    fn next(&self, block: usize) -> Option<usize> {
+        match self.edges[block].to {
+            Target::Block(next) => Some(next),
+            _ => None,
+        }
+    }
+
+    // Each cycle as its blocks in execution order, starting from the block
+    // the cycle is entered at first when walking from its lowest block.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        // 0 unvisited, 1 on the current walk, 2 done.
+        let mut state = vec![0u8; self.blocks.len()];
+        let mut cycles = Vec::new();
+        for first in 0..self.blocks.len() {
+            let mut walk = Vec::new();
+            let mut current = Some(first);
+            while let Some(block) = current.filter(|&b| state[b] == 0) {
+                state[block] = 1;
+                walk.push(block);
+                current = self.next(block);
+            }
+            if let Some(block) = current.filter(|&b| state[b] == 1) {
+                let start = walk.iter().position(|&b| b == block).unwrap();
+                cycles.push(walk[start..].to_vec());
+            }
+            for block in walk {
+                state[block] = 2;
+            }
+        }
+        cycles
+    }
+
+    // Blocks that no execution starting at pc 0 can get to.
+    pub fn unreachable(&self) -> Vec<usize> {
+        let mut reached = vec![false; self.blocks.len()];
+        let mut current = if self.blocks.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        while let Some(block) = current.filter(|&b| !reached[b]) {
+            reached[block] = true;
+            current = self.next(block);
+        }
+        (0..self.blocks.len()).filter(|&b| !reached[b]).collect()
+    }
+
+    // Graphviz source. Jumps are solid and fallthroughs dashed, cycle edges
+    // are red and unreachable blocks greyed out.
+    pub fn to_dot(&self) -> String {
+        let in_cycle: Vec<usize> = self.cycles().into_iter().flatten().collect();
+        let unreachable = self.unreachable();
+        let mut dot = String::from("digraph program {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let label: String = (block.start..block.end)
+                .map(|pc| format!("{}: {}\\l", pc, self.program[pc]))
+                .collect();
+            let style = if unreachable.contains(&i) {
+                ", style=filled, fillcolor=lightgrey, fontcolor=grey40"
+            } else {
+                ""
+            };
+            writeln!(dot, "    b{} [label=\"{}\"{}];", block.start, label, style).unwrap();
+        }
+        if self.edges.iter().any(|e| e.to == Target::Exit) {
+            dot.push_str("    exit [shape=doublecircle];\n");
+        }
+
+        for edge in self.edges.iter() {
+            let to = match edge.to {
+                Target::Block(b) => format!("b{}", self.blocks[b].start),
+                Target::Exit => String::from("exit"),
+                Target::OutOfBounds(pc) => {
+                    let node = format!("out{}", pc.to_string().replace('-', "_"));
+                    writeln!(
+                        dot,
+                        "    {} [label=\"pc {}\", shape=octagon, color=orange];",
+                        node, pc
+                    )
+                    .unwrap();
+                    node
+                }
+            };
+            let mut attributes = Vec::new();
+            if edge.kind == EdgeKind::Fallthrough {
+                attributes.push("style=dashed");
+            }
+            if in_cycle.contains(&edge.from) {
+                attributes.push("color=red");
+            }
+            let from = format!("b{}", self.blocks[edge.from].start);
+            if attributes.is_empty() {
+                writeln!(dot, "    {} -> {};", from, to).unwrap();
+            } else {
+                writeln!(dot, "    {} -> {} [{}];", from, to, attributes.join(", ")).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::assemble;
+
+    #[test]
+    fn test_reaching() {
+        let program =
+            assemble("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6")
+                .unwrap();
+        let cfg = Cfg::new(&program);
+
+        assert_eq!(9, cfg.exit());
+        assert_eq!(&[2, 5], cfg.predecessors(6));
+        assert_eq!(
+            vec![false, false, false, false, false, false, false, false, true, true],
+            cfg.reaching(cfg.exit())
+        );
+
+        let cfg = Cfg::new(&assemble("jmp -1\njmp +3\nacc +1").unwrap());
+        assert!(cfg.predecessors(0).is_empty());
+        assert_eq!(vec![false, false, true, true], cfg.reaching(3));
+    }
+
+    #[test]
+    fn test_blocks() {
+        let program =
+            assemble("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6")
+                .unwrap();
+        let graph = BlockGraph::new(&program);
+
+        assert_eq!(
+            vec![(0, 1), (1, 3), (3, 5), (5, 6), (6, 8), (8, 9)],
+            graph
+                .blocks
+                .iter()
+                .map(|b| (b.start, b.end))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                (Target::Block(1), EdgeKind::Fallthrough),
+                (Target::Block(4), EdgeKind::Jump),
+                (Target::Block(1), EdgeKind::Jump),
+                (Target::Block(4), EdgeKind::Fallthrough),
+                (Target::Block(2), EdgeKind::Jump),
+                (Target::Exit, EdgeKind::Fallthrough),
+            ],
+            graph
+                .edges
+                .iter()
+                .map(|e| (e.to, e.kind))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![vec![1, 4, 2]], graph.cycles());
+        assert_eq!(vec![3, 5], graph.unreachable());
+
+        let graph = BlockGraph::new(&assemble("jmp +0\nacc +1\njmp -5").unwrap());
+        assert_eq!(vec![vec![0]], graph.cycles());
+        assert_eq!(Target::OutOfBounds(-3), graph.edges[1].to);
+    }
+
+    #[test]
+    fn test_dot() {
+        let program = assemble("acc +1\njmp +2\nacc +2\nnop +0\njmp -4\njmp -9").unwrap();
+        let dot = BlockGraph::new(&program).to_dot();
+
+        assert!(dot.starts_with("digraph program {\n"));
+        assert!(dot.contains("    b0 [label=\"0: acc +1\\l1: jmp +2\\l\"];\n"));
+        assert!(dot.contains("    b2 [label=\"2: acc +2\\l\", style=filled"));
+        assert!(dot.contains("    b0 -> b3 [color=red];\n"));
+        assert!(dot.contains("    b2 -> b3 [style=dashed];\n"));
+        assert!(dot.contains("    b3 -> b0 [color=red];\n"));
+        assert!(dot.contains("    out_4 [label=\"pc -4\", shape=octagon, color=orange];\n"));
+        assert!(dot.contains("    b5 -> out_4;\n"));
+        assert!(!dot.contains("exit"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
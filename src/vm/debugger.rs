@@ -0,0 +1,355 @@
+use super::{disassemble, repair, ExitMode, Instruction, Machine};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    io::{self, Write},
+};
+
+// One executed instruction and the state it left behind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub acc: i32,
+    pub next_pc: isize,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>5}  {:<10} acc={} pc={}",
+            self.pc,
+            self.instruction.to_string(),
+            self.acc,
+            self.next_pc
+        )
+    }
+}
+
+// Why `resume` gave control back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Breakpoint { pc: usize },
+    AccChanged { pc: usize, from: i32, to: i32 },
+    Exited(ExitMode),
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Breakpoint { pc } => write!(f, "breakpoint at pc {}", pc),
+            Event::AccChanged { pc, from, to } => {
+                write!(f, "acc changed from {} to {} at pc {}", from, to, pc)
+            }
+            Event::Exited(exit) => write!(f, "{}", exit),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    machine: Machine,
+    breakpoints: BTreeSet<usize>,
+    watch_acc: bool,
+    trace: Vec<TraceEntry>,
+    // The breakpoint `resume` last stopped at, cleared by the next step.
+    stopped_at: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new(machine: Machine) -> Self {
+        Debugger {
+            machine,
+            breakpoints: BTreeSet::new(),
+            watch_acc: false,
+            trace: Vec::new(),
+            stopped_at: None,
+        }
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    pub fn remove_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    pub fn watch_acc(&mut self, watch: bool) {
+        self.watch_acc = watch;
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        &self.trace
+    }
+
+    pub fn step(&mut self) -> Result<TraceEntry, ExitMode> {
+        let pc = self.machine.pc as usize;
+        self.stopped_at = None;
+        let instruction = self.machine.step()?;
+        let entry = TraceEntry {
+            pc,
+            instruction,
+            acc: self.machine.acc,
+            next_pc: self.machine.pc,
+        };
+        self.trace.push(entry);
+        Ok(entry)
+    }
+
+    // Runs until a breakpoint is reached, the watched accumulator changes or
+    // the machine halts. Resuming from a breakpoint stop skips that one
+    // breakpoint, so it always makes progress.
+    pub fn resume(&mut self) -> Event {
+        loop {
+            let pc = self.machine.pc as usize;
+            if self.machine.status().is_none()
+                && self.breakpoints.contains(&pc)
+                && self.stopped_at != Some(pc)
+            {
+                self.stopped_at = Some(pc);
+                return Event::Breakpoint { pc };
+            }
+
+            let before = self.machine.acc;
+            match self.step() {
+                Err(exit) => return Event::Exited(exit),
+                Ok(entry) if self.watch_acc && entry.acc != before => {
+                    return Event::AccChanged {
+                        pc: entry.pc,
+                        from: before,
+                        to: entry.acc,
+                    }
+                }
+                Ok(_) => {}
+            }
+        }
+    }
+
+    fn report_exit<W: Write>(&self, exit: ExitMode, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", exit)?;
+        if let ExitMode::Loop { .. } = exit {
+            let visited: Vec<String> = self
+                .machine
+                .visited()
+                .iter()
+                .map(|pc| pc.to_string())
+                .collect();
+            writeln!(out, "visited: {}", visited.join(" "))?;
+        }
+        Ok(())
+    }
+
+    // Runs one command of the `debug` prompt, returns false once asked to quit.
+    pub fn execute<W: Write>(&mut self, command: &str, out: &mut W) -> io::Result<bool> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let pc_arg = || words.get(1).and_then(|w| w.parse::<usize>().ok());
+
+        match words.first().copied() {
+            None => {}
+            Some("s") | Some("step") => {
+                let n = match words.get(1).map(|w| w.parse::<usize>()) {
+                    None => 1,
+                    Some(Ok(n)) => n,
+                    Some(Err(_)) => {
+                        writeln!(out, "usage: step [n]")?;
+                        return Ok(true);
+                    }
+                };
+                for _ in 0..n {
+                    match self.step() {
+                        Ok(entry) => writeln!(out, "{}", entry)?,
+                        Err(exit) => {
+                            self.report_exit(exit, out)?;
+                            break;
+                        }
+                    }
+                }
+            }
+            Some("c") | Some("continue") => match self.resume() {
+                Event::Exited(exit) => self.report_exit(exit, out)?,
+                event => writeln!(out, "{}", event)?,
+            },
+            Some("b") | Some("break") => match pc_arg() {
+                Some(pc) => {
+                    self.add_breakpoint(pc);
+                    writeln!(out, "breakpoint at pc {}", pc)?
+                }
+                None => {
+                    let pcs: Vec<String> =
+                        self.breakpoints.iter().map(|pc| pc.to_string()).collect();
+                    writeln!(out, "breakpoints: {}", pcs.join(" "))?
+                }
+            },
+            Some("d") | Some("delete") => match pc_arg() {
+                Some(pc) if self.remove_breakpoint(pc) => writeln!(out, "deleted pc {}", pc)?,
+                _ => writeln!(out, "no such breakpoint")?,
+            },
+            Some("w") | Some("watch") => {
+                self.watch_acc = !self.watch_acc;
+                let state = if self.watch_acc { "on" } else { "off" };
+                writeln!(out, "watching acc: {}", state)?
+            }
+            Some("p") | Some("print") => {
+                let machine = self.machine();
+                write!(
+                    out,
+                    "pc={} acc={} steps={}",
+                    machine.pc,
+                    machine.acc,
+                    machine.steps()
+                )?;
+                match machine.current() {
+                    Some(instruction) => writeln!(out, " next: {}", instruction)?,
+                    None => writeln!(out)?,
+                }
+            }
+            Some("t") | Some("trace") => {
+                for entry in self.trace() {
+                    writeln!(out, "{}", entry)?;
+                }
+            }
+            Some("l") | Some("list") => {
+                // `>` marks the next instruction, `*` a breakpoint.
+                let program = disassemble(self.machine.program());
+                for (pc, line) in program.lines().enumerate() {
+                    let next = if pc as isize == self.machine.pc {
+                        '>'
+                    } else {
+                        ' '
+                    };
+                    let stop = if self.breakpoints.contains(&pc) {
+                        '*'
+                    } else {
+                        ' '
+                    };
+                    writeln!(out, "{}{}{:>4}  {}", next, stop, pc, line)?;
+                }
+            }
+            Some("v") | Some("visited") => {
+                let visited: Vec<String> = self
+                    .machine
+                    .visited()
+                    .iter()
+                    .map(|pc| pc.to_string())
+                    .collect();
+                writeln!(out, "visited: {}", visited.join(" "))?
+            }
+            Some("r") | Some("repair") => match repair(self.machine.program()) {
+                Some(repair) => writeln!(out, "repair: {}", repair)?,
+                None => writeln!(out, "no single jmp/nop flip makes it terminate")?,
+            },
+            Some("q") | Some("quit") => return Ok(false),
+            Some(other) => writeln!(
+                out,
+                "unknown command {:?}, try step [n], continue, break [pc], delete pc, \
+                 watch, print, list, trace, visited, repair or quit",
+                other
+            )?,
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::assemble;
+
+    fn example() -> Machine {
+        let source = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+        Machine::new(assemble(source).unwrap())
+    }
+
+    #[test]
+    fn test_breakpoints_and_watch() {
+        let mut debugger = Debugger::new(example());
+        debugger.add_breakpoint(7);
+        debugger.add_breakpoint(0);
+
+        // A breakpoint on the starting pc stops before anything runs.
+        assert_eq!(Event::Breakpoint { pc: 0 }, debugger.resume());
+        assert!(debugger.trace().is_empty());
+        assert_eq!(Event::Breakpoint { pc: 7 }, debugger.resume());
+        assert_eq!((2, 7), (debugger.machine().acc, debugger.machine().pc));
+
+        debugger.watch_acc(true);
+        assert_eq!(
+            Event::AccChanged {
+                pc: 3,
+                from: 2,
+                to: 5
+            },
+            debugger.resume()
+        );
+        debugger.watch_acc(false);
+        assert_eq!(Event::Exited(ExitMode::Loop { pc: 1 }), debugger.resume());
+
+        assert_eq!(
+            vec![0, 1, 2, 6, 7, 3, 4],
+            debugger.trace().iter().map(|e| e.pc).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            TraceEntry {
+                pc: 7,
+                instruction: Instruction::Jmp(-4),
+                acc: 2,
+                next_pc: 3
+            },
+            debugger.trace()[4]
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut debugger = Debugger::new(example());
+        let mut out = Vec::new();
+        let commands = [
+            "step 2", "break 4", "continue", "print", "continue", "bogus", "repair", "list",
+        ];
+        for command in commands.iter() {
+            assert!(debugger.execute(command, &mut out).unwrap());
+        }
+        assert!(!debugger.execute("quit", &mut out).unwrap());
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!("    0  nop +0     acc=0 pc=1", lines[0]);
+        assert_eq!("    1  acc +1     acc=1 pc=2", lines[1]);
+        assert_eq!("breakpoint at pc 4", lines[2]);
+        assert_eq!("breakpoint at pc 4", lines[3]);
+        assert_eq!("pc=4 acc=5 steps=6 next: jmp -3", lines[4]);
+        assert_eq!("loop detected at pc 1", lines[5]);
+        assert_eq!("visited: 0 1 2 3 4 6 7", lines[6]);
+        assert!(lines[7].starts_with("unknown command \"bogus\""));
+        assert_eq!(
+            "repair: pc 7: jmp -4 -> nop -4, terminates with acc=8",
+            lines[8]
+        );
+        assert_eq!("     0  nop +0", lines[9]);
+        assert_eq!(">    1  acc +1", lines[10]);
+        assert_eq!(" *   4  jmp -3", lines[13]);
+        assert_eq!(18, lines.len());
+    }
+
+    #[test]
+    fn test_break_on_start() {
+        let mut debugger = Debugger::new(example());
+        let mut out = Vec::new();
+        for command in ["step foo", "break 0", "continue", "continue", "print"].iter() {
+            debugger.execute(command, &mut out).unwrap();
+        }
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!("usage: step [n]", lines[0]);
+        assert_eq!("breakpoint at pc 0", lines[2]);
+        assert_eq!("loop detected at pc 1", lines[3]);
+        assert!(lines[5].starts_with("pc=1 acc=5"));
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::{self, Display, Formatter};
+
+mod asm;
+mod cfg;
+mod debugger;
+mod repair;
+pub use asm::{assemble, disassemble};
+pub use cfg::{BlockGraph, Cfg};
+pub use debugger::Debugger;
+pub use repair::repair;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Nop(i32),
+    Acc(i32),
+    Jmp(i32),
+}
+
+impl Instruction {
+    // How much the accumulator and the pc move when this is executed.
+    pub fn effect(self) -> (i32, isize) {
+        match self {
+            Instruction::Nop(_) => (0, 1),
+            Instruction::Acc(v) => (v, 1),
+            Instruction::Jmp(v) => (0, v as isize),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Nop(v) => write!(f, "nop {:+}", v),
+            Instruction::Acc(v) => write!(f, "acc {:+}", v),
+            Instruction::Jmp(v) => write!(f, "jmp {:+}", v),
+        }
+    }
+}
+
+pub type Program = Vec<Instruction>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitMode {
+    // The pc landed right after the last instruction.
+    Normal,
+    // The instruction at `pc` was about to run a second time.
+    Loop { pc: usize },
+    // The pc went anywhere else outside the program.
+    OutOfBounds { pc: isize },
+    StepLimit { steps: usize },
+}
+
+impl Display for ExitMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitMode::Normal => write!(f, "terminated normally"),
+            ExitMode::Loop { pc } => write!(f, "loop detected at pc {}", pc),
+            ExitMode::OutOfBounds { pc } => write!(f, "jumped out of bounds to {}", pc),
+            ExitMode::StepLimit { steps } => write!(f, "stopped after {} steps", steps),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine {
+    pub acc: i32,
+    pub pc: isize,
+    program: Program,
+    visited: Vec<bool>,
+    steps: usize,
+    detect_loops: bool,
+    step_limit: Option<usize>,
+}
+
+impl Machine {
+    // Stops on loops by default, with no step limit.
+    pub fn new(program: Program) -> Self {
+        Machine {
+            acc: 0,
+            pc: 0,
+            visited: vec![false; program.len()],
+            program,
+            steps: 0,
+            detect_loops: true,
+            step_limit: None,
+        }
+    }
+
+    pub fn detect_loops(mut self, detect: bool) -> Self {
+        self.detect_loops = detect;
+        self
+    }
+
+    pub fn step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // The instruction the next step would run.
+    pub fn current(&self) -> Option<Instruction> {
+        if self.pc < 0 {
+            return None;
+        }
+        self.program.get(self.pc as usize).copied()
+    }
+
+    // Every pc executed so far, in program order.
+    pub fn visited(&self) -> Vec<usize> {
+        (0..self.program.len())
+            .filter(|&pc| self.visited[pc])
+            .collect()
+    }
+
+    // Why the machine can't go on from its current state, if it can't.
+    pub fn status(&self) -> Option<ExitMode> {
+        let len = self.program.len() as isize;
+        if self.pc == len {
+            Some(ExitMode::Normal)
+        } else if self.pc < 0 || self.pc > len {
+            Some(ExitMode::OutOfBounds { pc: self.pc })
+        } else if self.detect_loops && self.visited[self.pc as usize] {
+            Some(ExitMode::Loop {
+                pc: self.pc as usize,
+            })
+        } else if self.step_limit.is_some_and(|limit| self.steps >= limit) {
+            Some(ExitMode::StepLimit { steps: self.steps })
+        } else {
+            None
+        }
+    }
+
+    // Runs one instruction, unless a halting condition already holds.
+    pub fn step(&mut self) -> Result<Instruction, ExitMode> {
+        if let Some(exit) = self.status() {
+            return Err(exit);
+        }
+        let pc = self.pc as usize;
+        let instruction = self.program[pc];
+        let (acc, jump) = instruction.effect();
+        self.visited[pc] = true;
+        self.steps += 1;
+        self.acc += acc;
+        self.pc += jump;
+        Ok(instruction)
+    }
+
+    pub fn run(&mut self) -> ExitMode {
+        loop {
+            if let Err(exit) = self.step() {
+                return exit;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Instruction::*;
+
+    fn example() -> Program {
+        vec![
+            Nop(0),
+            Acc(1),
+            Jmp(4),
+            Acc(3),
+            Jmp(-3),
+            Acc(-99),
+            Acc(1),
+            Jmp(-4),
+            Acc(6),
+        ]
+    }
+
+    #[test]
+    fn test_effect() {
+        assert_eq!((0, 1), Nop(10).effect());
+        assert_eq!((10, 1), Acc(10).effect());
+        assert_eq!((0, 10), Jmp(10).effect());
+        assert_eq!((0, -10), Jmp(-10).effect());
+    }
+
+    #[test]
+    fn test_step() {
+        let mut machine = Machine::new(example());
+        assert_eq!(Ok(Nop(0)), machine.step());
+        assert_eq!(Ok(Acc(1)), machine.step());
+        assert_eq!(Ok(Jmp(4)), machine.step());
+        assert_eq!((1, 6), (machine.acc, machine.pc));
+        assert_eq!(3, machine.steps());
+    }
+
+    #[test]
+    fn test_exit_modes() {
+        let mut machine = Machine::new(example());
+        assert_eq!(ExitMode::Loop { pc: 1 }, machine.run());
+        assert_eq!(5, machine.acc);
+        // Halted machines stay halted.
+        assert_eq!(Err(ExitMode::Loop { pc: 1 }), machine.step());
+
+        let mut fixed = example();
+        fixed[7] = Nop(-4);
+        let mut machine = Machine::new(fixed);
+        assert_eq!(ExitMode::Normal, machine.run());
+        assert_eq!(8, machine.acc);
+
+        let mut machine = Machine::new(vec![Acc(1), Jmp(-2)]);
+        assert_eq!(ExitMode::OutOfBounds { pc: -1 }, machine.run());
+        let mut machine = Machine::new(vec![Jmp(3), Acc(1)]);
+        assert_eq!(ExitMode::OutOfBounds { pc: 3 }, machine.run());
+
+        let mut machine = Machine::new(example()).detect_loops(false).step_limit(100);
+        assert_eq!(ExitMode::StepLimit { steps: 100 }, machine.run());
+    }
+}
@@ -0,0 +1,112 @@
+use super::{cfg::successor, Cfg, ExitMode, Instruction, Machine};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Repair {
+    pub pc: usize,
+    pub original: Instruction,
+    pub patched: Instruction,
+    // The accumulator once the patched program terminates.
+    pub acc: i32,
+}
+
+impl Display for Repair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pc {}: {} -> {}, terminates with acc={}",
+            self.pc, self.original, self.patched, self.acc
+        )
+    }
+}
+
+fn flipped(instruction: Instruction) -> Option<Instruction> {
+    match instruction {
+        Instruction::Jmp(v) => Some(Instruction::Nop(v)),
+        Instruction::Nop(v) => Some(Instruction::Jmp(v)),
+        Instruction::Acc(_) => None,
+    }
+}
+
+// Finds the jmp/nop flip that makes a looping program terminate in linear
+// time: only instructions on the original path matter, and a flip works
+// exactly when it leads somewhere that already reaches the end. None of
+// those nodes can depend on the flipped instruction, since it lies on a path
+// that never terminates. Programs that already terminate need no repair.
+pub fn repair(program: &[Instruction]) -> Option<Repair> {
+    let cfg = Cfg::new(program);
+    let terminates = cfg.reaching(cfg.exit());
+    if terminates[0] {
+        return None;
+    }
+
+    let mut machine = Machine::new(program.to_vec());
+    let (pc, original, patched) = loop {
+        let pc = machine.pc as usize;
+        let instruction = machine.step().ok()?;
+        if let Some(patched) = flipped(instruction) {
+            let fixed = successor(patched, pc, program.len());
+            if fixed.is_some_and(|next| terminates[next]) {
+                break (pc, instruction, patched);
+            }
+        }
+    };
+
+    let mut fixed = program.to_vec();
+    fixed[pc] = patched;
+    let mut machine = Machine::new(fixed);
+    match machine.run() {
+        ExitMode::Normal => Some(Repair {
+            pc,
+            original,
+            patched,
+            acc: machine.acc,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::assemble;
+    use Instruction::*;
+
+    #[test]
+    fn test_repair() {
+        let program =
+            assemble("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6")
+                .unwrap();
+        assert_eq!(
+            Some(Repair {
+                pc: 7,
+                original: Jmp(-4),
+                patched: Nop(-4),
+                acc: 8
+            }),
+            repair(&program)
+        );
+        assert_eq!(
+            "pc 7: jmp -4 -> nop -4, terminates with acc=8",
+            repair(&program).unwrap().to_string()
+        );
+
+        // A nop becoming a jmp, over code that would loop if run in order.
+        let program = assemble("nop +3\njmp +0\nacc +5\nacc +2").unwrap();
+        assert_eq!(
+            Some(Repair {
+                pc: 0,
+                original: Nop(3),
+                patched: Jmp(3),
+                acc: 2
+            }),
+            repair(&program)
+        );
+    }
+
+    #[test]
+    fn test_no_repair() {
+        assert_eq!(None, repair(&assemble("acc +1\nnop +0").unwrap()));
+        assert_eq!(None, repair(&assemble("acc +1\njmp -1\njmp -2").unwrap()));
+    }
+}